@@ -0,0 +1,29 @@
+use criterion::{criterion_group, criterion_main, Criterion, Throughput};
+use lox::scanner::Scanner;
+use std::hint::black_box;
+
+/// About 1 MB of Lox mixing every kind of token, including non-ASCII text.
+fn script() -> String {
+    let chunk = "// Compute a few fibonacci numbers, ünïcode included\n\
+                 fun fib(n) {\n\
+                 \x20 if (n <= 1) return n;\n\
+                 \x20 return fib(n - 2) + fib(n - 1);\n\
+                 }\n\
+                 var greeting = \"héllo 😀\";\n\
+                 for (var i = 0; i < 20; i = i + 1) { print fib(i) * 1.5; }\n";
+    chunk.repeat(1024 * 1024 / chunk.len())
+}
+
+fn scanner_benchmark(c: &mut Criterion) {
+    let source = script();
+    let mut group = c.benchmark_group("scanner");
+    group.throughput(Throughput::Bytes(source.len() as u64));
+    group.sample_size(10);
+    group.bench_function("scan 1 MB", |b| {
+        b.iter(|| Scanner::new(black_box(&source)).scan_tokens().unwrap())
+    });
+    group.finish();
+}
+
+criterion_group!(benches, scanner_benchmark);
+criterion_main!(benches);
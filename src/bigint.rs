@@ -0,0 +1,329 @@
+use std::{cmp::Ordering, fmt::Display};
+
+/// Arbitrary-precision signed integer, the value of Lox integers that outgrow an
+/// `i64`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub struct BigInt {
+    negative: bool,
+    /// Magnitude in base 2^32, least significant digit first, without leading
+    /// zeros. Zero has no digits and is never negative.
+    digits: Vec<u32>,
+}
+
+const BASE: f64 = 4294967296.0;
+
+impl BigInt {
+    fn new(negative: bool, mut digits: Vec<u32>) -> Self {
+        while digits.last() == Some(&0) {
+            digits.pop();
+        }
+        BigInt {
+            negative: negative && !digits.is_empty(),
+            digits,
+        }
+    }
+
+    /// Parses a string of decimal digits, with an optional leading `-`.
+    pub fn parse(text: &str) -> Option<Self> {
+        BigInt::parse_radix(text, 10)
+    }
+
+    /// Parses a string of digits in `radix`, with an optional leading `-`.
+    pub fn parse_radix(text: &str, radix: u32) -> Option<Self> {
+        let (negative, text) = match text.strip_prefix('-') {
+            Some(rest) => (true, rest),
+            None => (false, text),
+        };
+        if text.is_empty() {
+            return None;
+        }
+        let mut digits = Vec::new();
+        for ch in text.chars() {
+            let digit = ch.to_digit(radix)?;
+            digits = add_small(&mul_small(&digits, radix), digit);
+        }
+        Some(BigInt::new(negative, digits))
+    }
+
+    /// The integral part of `value`, or `None` for infinities and NaN.
+    pub fn from_f64(value: f64) -> Option<Self> {
+        if !value.is_finite() {
+            return None;
+        }
+        let mut rest = value.trunc().abs();
+        let mut digits = Vec::new();
+        while rest > 0.0 {
+            digits.push((rest % BASE) as u32);
+            rest = (rest / BASE).floor();
+        }
+        Some(BigInt::new(value < 0.0, digits))
+    }
+
+    pub fn to_i64(&self) -> Option<i64> {
+        if self.digits.len() > 2 {
+            return None;
+        }
+        let magnitude = self
+            .digits
+            .iter()
+            .rev()
+            .fold(0i128, |acc, &digit| (acc << 32) | digit as i128);
+        i64::try_from(if self.negative { -magnitude } else { magnitude }).ok()
+    }
+
+    pub fn to_f64(&self) -> f64 {
+        let magnitude = self
+            .digits
+            .iter()
+            .rev()
+            .fold(0.0, |acc, &digit| acc * BASE + digit as f64);
+        if self.negative {
+            -magnitude
+        } else {
+            magnitude
+        }
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.digits.is_empty()
+    }
+
+    pub fn neg(&self) -> Self {
+        BigInt::new(!self.negative, self.digits.clone())
+    }
+
+    pub fn add(&self, other: &Self) -> Self {
+        if self.negative == other.negative {
+            return BigInt::new(self.negative, add(&self.digits, &other.digits));
+        }
+        match compare(&self.digits, &other.digits) {
+            Ordering::Less => BigInt::new(other.negative, sub(&other.digits, &self.digits)),
+            _ => BigInt::new(self.negative, sub(&self.digits, &other.digits)),
+        }
+    }
+
+    pub fn sub(&self, other: &Self) -> Self {
+        self.add(&other.neg())
+    }
+
+    pub fn mul(&self, other: &Self) -> Self {
+        BigInt::new(
+            self.negative != other.negative,
+            mul(&self.digits, &other.digits),
+        )
+    }
+
+    /// Quotient and remainder of a division truncating towards zero, so the
+    /// remainder has the sign of `self`. `None` when dividing by zero.
+    pub fn div_rem(&self, other: &Self) -> Option<(Self, Self)> {
+        if other.is_zero() {
+            return None;
+        }
+        let (quotient, remainder) = div_rem(&self.digits, &other.digits);
+        Some((
+            BigInt::new(self.negative != other.negative, quotient),
+            BigInt::new(self.negative, remainder),
+        ))
+    }
+}
+
+impl From<i64> for BigInt {
+    fn from(value: i64) -> Self {
+        let magnitude = value.unsigned_abs();
+        BigInt::new(value < 0, vec![magnitude as u32, (magnitude >> 32) as u32])
+    }
+}
+
+impl Ord for BigInt {
+    fn cmp(&self, other: &Self) -> Ordering {
+        match (self.negative, other.negative) {
+            (false, true) => Ordering::Greater,
+            (true, false) => Ordering::Less,
+            (false, false) => compare(&self.digits, &other.digits),
+            (true, true) => compare(&other.digits, &self.digits),
+        }
+    }
+}
+
+impl PartialOrd for BigInt {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Display for BigInt {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.is_zero() {
+            return write!(f, "0");
+        }
+        // Peel off nine decimal digits at a time, lowest first
+        let mut chunks = Vec::new();
+        let mut rest = self.digits.clone();
+        while !rest.is_empty() {
+            let (quotient, remainder) = div_rem_small(&rest, 1_000_000_000);
+            chunks.push(remainder);
+            rest = quotient;
+        }
+        if self.negative {
+            write!(f, "-")?;
+        }
+        write!(f, "{}", chunks.pop().unwrap_or(0))?;
+        for chunk in chunks.iter().rev() {
+            write!(f, "{:09}", chunk)?;
+        }
+        Ok(())
+    }
+}
+
+fn trim(mut digits: Vec<u32>) -> Vec<u32> {
+    while digits.last() == Some(&0) {
+        digits.pop();
+    }
+    digits
+}
+
+fn compare(a: &[u32], b: &[u32]) -> Ordering {
+    a.len()
+        .cmp(&b.len())
+        .then_with(|| a.iter().rev().cmp(b.iter().rev()))
+}
+
+fn add(a: &[u32], b: &[u32]) -> Vec<u32> {
+    let mut result = Vec::with_capacity(a.len().max(b.len()) + 1);
+    let mut carry = 0u64;
+    for i in 0..a.len().max(b.len()) {
+        let sum = *a.get(i).unwrap_or(&0) as u64 + *b.get(i).unwrap_or(&0) as u64 + carry;
+        result.push(sum as u32);
+        carry = sum >> 32;
+    }
+    result.push(carry as u32);
+    trim(result)
+}
+
+/// `a - b`, where `a` must not be smaller than `b`.
+fn sub(a: &[u32], b: &[u32]) -> Vec<u32> {
+    let mut result = Vec::with_capacity(a.len());
+    let mut borrow = 0i64;
+    for (i, &digit) in a.iter().enumerate() {
+        let mut diff = digit as i64 - *b.get(i).unwrap_or(&0) as i64 - borrow;
+        borrow = 0;
+        if diff < 0 {
+            diff += 1 << 32;
+            borrow = 1;
+        }
+        result.push(diff as u32);
+    }
+    trim(result)
+}
+
+fn mul(a: &[u32], b: &[u32]) -> Vec<u32> {
+    let mut result = vec![0u32; a.len() + b.len()];
+    for (i, &x) in a.iter().enumerate() {
+        let mut carry = 0u64;
+        for (j, &y) in b.iter().enumerate() {
+            let product = x as u64 * y as u64 + result[i + j] as u64 + carry;
+            result[i + j] = product as u32;
+            carry = product >> 32;
+        }
+        result[i + b.len()] = carry as u32;
+    }
+    trim(result)
+}
+
+fn mul_small(a: &[u32], factor: u32) -> Vec<u32> {
+    mul(a, &[factor])
+}
+
+fn add_small(a: &[u32], term: u32) -> Vec<u32> {
+    add(a, &[term])
+}
+
+fn div_rem_small(a: &[u32], divisor: u32) -> (Vec<u32>, u32) {
+    let mut quotient = vec![0u32; a.len()];
+    let mut remainder = 0u64;
+    for i in (0..a.len()).rev() {
+        let current = (remainder << 32) | a[i] as u64;
+        quotient[i] = (current / divisor as u64) as u32;
+        remainder = current % divisor as u64;
+    }
+    (trim(quotient), remainder as u32)
+}
+
+/// `digits` shifted left by `shift` bits, less than 32, with one more digit on top
+/// for what is shifted out.
+fn shift_left(digits: &[u32], shift: u32) -> Vec<u32> {
+    let mut result = Vec::with_capacity(digits.len() + 1);
+    let mut carry = 0u32;
+    for &digit in digits {
+        let wide = (digit as u64) << shift;
+        result.push(wide as u32 | carry);
+        carry = (wide >> 32) as u32;
+    }
+    result.push(carry);
+    result
+}
+
+/// Long division in base 2^32 (Knuth's algorithm D), `b` must not be zero.
+fn div_rem(a: &[u32], b: &[u32]) -> (Vec<u32>, Vec<u32>) {
+    if compare(a, b) == Ordering::Less {
+        return (Vec::new(), a.to_vec());
+    }
+    if b.len() == 1 {
+        let (quotient, remainder) = div_rem_small(a, b[0]);
+        return (quotient, trim(vec![remainder]));
+    }
+    // Scale both so the top digit of the divisor has its high bit set, then each
+    // quotient digit estimated from the top digits is at most two too large
+    let shift = b[b.len() - 1].leading_zeros();
+    let mut b = shift_left(b, shift);
+    b.pop();
+    let mut a = shift_left(a, shift);
+    let n = b.len();
+    let mut quotient = vec![0u32; a.len() - n];
+    for j in (0..quotient.len()).rev() {
+        let top = (a[j + n] as u64) << 32 | a[j + n - 1] as u64;
+        let mut estimate = top / b[n - 1] as u64;
+        let mut rest = top % b[n - 1] as u64;
+        while estimate > u32::MAX as u64
+            || estimate * b[n - 2] as u64 > (rest << 32 | a[j + n - 2] as u64)
+        {
+            estimate -= 1;
+            rest += b[n - 1] as u64;
+            if rest > u32::MAX as u64 {
+                break;
+            }
+        }
+        // Subtract estimate * b from the digits of a it lines up with
+        let mut carry = 0u64;
+        let mut borrow = 0i64;
+        for i in 0..n {
+            let product = estimate * b[i] as u64 + carry;
+            carry = product >> 32;
+            let diff = a[i + j] as i64 - (product as u32) as i64 - borrow;
+            a[i + j] = diff as u32;
+            borrow = (diff < 0) as i64;
+        }
+        let diff = a[j + n] as i64 - carry as i64 - borrow;
+        a[j + n] = diff as u32;
+        // The estimate was still one too large, add b back
+        if diff < 0 {
+            estimate -= 1;
+            let mut carry = 0u64;
+            for i in 0..n {
+                let sum = a[i + j] as u64 + b[i] as u64 + carry;
+                a[i + j] = sum as u32;
+                carry = sum >> 32;
+            }
+            a[j + n] = a[j + n].wrapping_add(carry as u32);
+        }
+        quotient[j] = estimate as u32;
+    }
+    // What is left of a is the remainder, scaled up by the shift
+    let remainder = (0..n)
+        .map(|i| {
+            let high = if i + 1 < n { a[i + 1] as u64 } else { 0 };
+            ((high << 32 | a[i] as u64) >> shift) as u32
+        })
+        .collect();
+    (trim(quotient), trim(remainder))
+}
@@ -1,19 +1,102 @@
-use std::{cell::RefCell, cmp::Ordering, rc::Rc};
+use std::{cell::RefCell, cmp::Ordering, fmt::Display, rc::Rc};
 
 use crate::{
-    environment::Environment, instance::LoxInstance, interpreter::Interpreter, stmt::FunStmt,
-    tokens::LoxObject, utils::Clock,
+    environment::Environment,
+    exceptions::{RuntimeError, Unwind},
+    instance::LoxInstance,
+    interpreter::Interpreter,
+    stmt::FunStmt,
+    tokens::LoxObject,
 };
 
-pub enum LoxCallableType {
-    Fun(LoxFunction),
-    Clock(Clock),
+/// How many arguments a callable accepts.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Arity {
+    Exact(usize),
+    AtLeast(usize),
+    /// Between the two counts, inclusive.
+    Range(usize, usize),
+}
+
+impl Arity {
+    pub fn accepts(&self, count: usize) -> bool {
+        match *self {
+            Arity::Exact(n) => count == n,
+            Arity::AtLeast(n) => count >= n,
+            Arity::Range(min, max) => (min..=max).contains(&count),
+        }
+    }
+
+    /// The noun that follows the arity in error messages. jlox always says
+    /// "arguments" after an exact count, even 1, so that wording is kept.
+    pub fn noun(&self) -> &'static str {
+        match *self {
+            Arity::AtLeast(1) | Arity::Range(_, 1) => "argument",
+            _ => "arguments",
+        }
+    }
+}
+
+impl Display for Arity {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Arity::Exact(n) => write!(f, "{}", n),
+            Arity::AtLeast(n) => write!(f, "at least {}", n),
+            Arity::Range(min, max) => write!(f, "{} to {}", min, max),
+        }
+    }
 }
 
 pub trait LoxCallable: std::fmt::Debug {
-    fn call(&self, interpreter: &mut Interpreter, arguments: Vec<LoxObject>) -> LoxObject;
-    fn arity(&self) -> usize;
+    fn call(
+        &self,
+        interpreter: &mut Interpreter,
+        arguments: Vec<LoxObject>,
+    ) -> Result<LoxObject, RuntimeError>;
+    fn arity(&self) -> Arity;
     fn to_string(&self) -> String;
+    /// Name shown for the callable in stack traces.
+    fn name(&self) -> String;
+}
+
+/// Signature of the Rust closures behind native functions. Errors are reported at
+/// the call site.
+pub type NativeFn = dyn Fn(&mut Interpreter, Vec<LoxObject>) -> Result<LoxObject, String>;
+
+/// A function implemented in Rust, see `Interpreter::define_native`.
+#[derive(Clone)]
+pub struct NativeFunction {
+    pub name: String,
+    pub arity: Arity,
+    pub function: Rc<NativeFn>,
+}
+
+impl std::fmt::Debug for NativeFunction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "NativeFunction({})", self.name)
+    }
+}
+
+impl LoxCallable for NativeFunction {
+    fn call(
+        &self,
+        interpreter: &mut Interpreter,
+        arguments: Vec<LoxObject>,
+    ) -> Result<LoxObject, RuntimeError> {
+        (self.function)(interpreter, arguments).map_err(|msg| interpreter.error(&msg))
+    }
+
+    fn arity(&self) -> Arity {
+        self.arity
+    }
+
+    fn to_string(&self) -> String {
+        "<native fn>".to_string()
+    }
+
+    fn name(&self) -> String {
+        self.name.clone()
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -21,6 +104,9 @@ pub struct LoxFunction {
     pub declaration: FunStmt,
     pub closure: Rc<RefCell<Environment>>,
     pub is_init: bool,
+    /// Shared by the copies of one function value and nothing else, functions
+    /// declared in the same scope also share their closure.
+    pub id: Rc<()>,
 }
 
 impl PartialOrd for LoxFunction {
@@ -36,56 +122,78 @@ impl PartialEq for LoxFunction {
 }
 
 impl LoxFunction {
-    pub fn new(declaration: FunStmt, closure: Environment, is_init: bool) -> Self {
+    pub fn new(declaration: FunStmt, closure: Rc<RefCell<Environment>>, is_init: bool) -> Self {
         LoxFunction {
             declaration,
-            closure: Rc::new(RefCell::new(closure)),
+            closure,
             is_init,
+            id: Rc::new(()),
         }
     }
 
     pub fn bind(&self, instance: LoxInstance) -> Self {
         let mut environment = Environment::new_with_enclosing_rc(Rc::clone(&self.closure));
         environment.define("this".to_string(), LoxObject::Instance(instance));
-        LoxFunction::new(self.declaration.clone(), environment, self.is_init)
+        LoxFunction::new(
+            self.declaration.clone(),
+            Rc::new(RefCell::new(environment)),
+            self.is_init,
+        )
     }
 
-    pub fn arity(&self) -> usize {
-        self.declaration.params.len()
+    pub fn arity(&self) -> Arity {
+        match self.declaration.rest {
+            Some(_) => Arity::AtLeast(self.declaration.params.len()),
+            None => Arity::Exact(self.declaration.params.len()),
+        }
     }
 }
 
 impl LoxCallable for LoxFunction {
-    fn call(&self, interpreter: &mut Interpreter, arguments: Vec<LoxObject>) -> LoxObject {
+    fn call(
+        &self,
+        interpreter: &mut Interpreter,
+        arguments: Vec<LoxObject>,
+    ) -> Result<LoxObject, RuntimeError> {
         let mut environment = Environment::new_with_enclosing_rc(Rc::clone(&self.closure));
-        for i in 0..self.arity() {
-            environment.define(
-                self.declaration.params.get(i).unwrap().lexeme.clone(),
-                arguments.get(i).unwrap().clone(),
-            )
+        let mut arguments = arguments.into_iter();
+        for param in &self.declaration.params {
+            let argument = arguments.next().unwrap_or(LoxObject::None);
+            environment.define(param.lexeme.clone(), argument);
+        }
+        // Whatever is left over goes to the rest parameter
+        if let Some(rest) = &self.declaration.rest {
+            let rest_arguments = Rc::new(RefCell::new(arguments.collect()));
+            environment.define(rest.lexeme.clone(), LoxObject::List(rest_arguments));
         }
         let value = match interpreter.execute_fun(self.declaration.body.clone(), environment) {
             Ok(_) => LoxObject::None,
-            Err(x) => {
-                if self.is_init {
-                    let closure = self.closure.borrow_mut();
-                    return closure.get("this".to_string()).unwrap();
-                }
-                x.value
+            Err(Unwind::Return(value)) => value,
+            Err(Unwind::Throw(error) | Unwind::Error(error)) => return Err(error),
+            Err(Unwind::Break | Unwind::Continue) => {
+                unreachable!("the resolver keeps loop jumps inside loops")
             }
         };
         if self.is_init {
-            let closure = self.closure.borrow_mut();
-            return closure.get("this".to_string()).unwrap();
+            let closure = self.closure.borrow();
+            return Ok(closure
+                .values
+                .get("this")
+                .cloned()
+                .unwrap_or(LoxObject::None));
         }
-        value
+        Ok(value)
     }
 
-    fn arity(&self) -> usize {
+    fn arity(&self) -> Arity {
         self.arity()
     }
 
     fn to_string(&self) -> String {
         format!("<fun {}", self.declaration.name.lexeme.clone())
     }
+
+    fn name(&self) -> String {
+        self.declaration.name.lexeme.clone()
+    }
 }
@@ -1,7 +1,8 @@
-use std::{collections::HashMap, fmt::Display};
+use std::{collections::HashMap, fmt::Display, rc::Rc};
 
 use crate::{
-    callable::{LoxCallable, LoxFunction},
+    callable::{Arity, LoxCallable, LoxFunction},
+    exceptions::RuntimeError,
     instance::LoxInstance,
     tokens::LoxObject,
 };
@@ -11,6 +12,9 @@ pub struct LoxClass {
     pub name: String,
     pub superclass: Box<Option<LoxClass>>,
     pub methods: HashMap<String, LoxFunction>,
+    /// Shared by the copies of one class and nothing else, which is what makes
+    /// it the same class.
+    pub id: Rc<()>,
 }
 
 impl LoxClass {
@@ -23,6 +27,7 @@ impl LoxClass {
             name,
             superclass: Box::new(superclass),
             methods,
+            id: Rc::new(()),
         }
     }
 
@@ -40,6 +45,13 @@ impl LoxClass {
     }
 }
 
+/// Classes are copied around by value, the copies of one class share its `id`.
+impl PartialEq for LoxClass {
+    fn eq(&self, other: &Self) -> bool {
+        Rc::ptr_eq(&self.id, &other.id)
+    }
+}
+
 impl Display for LoxClass {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "{}", self.name)
@@ -51,25 +63,29 @@ impl LoxCallable for LoxClass {
         &self,
         interpreter: &mut crate::interpreter::Interpreter,
         arguments: Vec<crate::tokens::LoxObject>,
-    ) -> crate::tokens::LoxObject {
+    ) -> Result<LoxObject, RuntimeError> {
         let mut instance = LoxObject::Instance(LoxInstance::new(self.clone()));
         if let Some(init) = self.find_methods("init") {
             instance = init
                 .bind(LoxInstance::new(self.clone()))
-                .call(interpreter, arguments);
+                .call(interpreter, arguments)?;
         }
-        instance
+        Ok(instance)
     }
 
-    fn arity(&self) -> usize {
+    fn arity(&self) -> Arity {
         if let Some(init) = self.find_methods("init") {
             init.arity()
         } else {
-            0
+            Arity::Exact(0)
         }
     }
 
     fn to_string(&self) -> String {
         self.name.to_string()
     }
+
+    fn name(&self) -> String {
+        self.name.clone()
+    }
 }
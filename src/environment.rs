@@ -1,6 +1,9 @@
 use std::{cell::RefCell, collections::HashMap, fmt::Display, rc::Rc};
 
-use crate::{exceptions::RuntimeError, tokens::LoxObject};
+use crate::{
+    exceptions::RuntimeError,
+    tokens::{LoxObject, Token},
+};
 
 #[derive(Debug)]
 pub struct Environment {
@@ -34,25 +37,24 @@ impl Environment {
         self.values.insert(name, value);
     }
 
-    pub fn assign(&mut self, name: String, value: LoxObject) {
-        if self.values.get(&name).is_some() {
-            self.values.insert(name, value);
-            return;
+    pub fn assign(&mut self, name: &Token, value: LoxObject) -> Result<(), RuntimeError> {
+        if self.values.contains_key(&name.lexeme) {
+            self.values.insert(name.lexeme.clone(), value);
+            return Ok(());
         };
 
-        match &self.enclosing {
-            Some(x) => {
-                let mut env = x.borrow_mut();
-                (*env).assign(name, value);
-                return;
-            }
-            None => (),
+        if let Some(x) = &self.enclosing {
+            let mut env = x.borrow_mut();
+            return (*env).assign(name, value);
         };
-        panic!("Undefined variable '{}'.", name)
+        Err(RuntimeError::raise(
+            name.clone(),
+            &format!("Undefined variable '{}'.", name.lexeme),
+        ))
     }
 
-    pub fn get(&self, name: String) -> Result<LoxObject, RuntimeError> {
-        if let Some(x) = self.values.get(&name) {
+    pub fn get(&self, name: &Token) -> Result<LoxObject, RuntimeError> {
+        if let Some(x) = self.values.get(&name.lexeme) {
             return Ok(x.clone());
         };
 
@@ -61,53 +63,65 @@ impl Environment {
                 let env = x.borrow();
                 (*env).get(name)
             }
-            None => Err(RuntimeError {}),
+            None => Err(RuntimeError::raise(
+                name.clone(),
+                &format!("Undefined variable '{}'.", name.lexeme),
+            )),
         }
     }
 
-    pub fn get_at(&self, distance: usize, name: String) -> LoxObject {
-        if distance == 0 {
-            match self.values.get(&name) {
-                Some(x) => x.clone(),
-                None => LoxObject::None,
-            }
+    /// The value of `name` in the scope `distance` levels out, where the resolver
+    /// found its declaration. A declaration that never ran, like one after a
+    /// runtime error in an earlier REPL input, leaves the name undefined.
+    pub fn get_at(&self, distance: usize, name: &Token) -> Result<LoxObject, RuntimeError> {
+        let value = if distance == 0 {
+            self.values.get(&name.lexeme).cloned()
         } else {
             match &self.enclosing {
-                Some(env) => self.get_at_helper(Rc::clone(env), distance - 1, name),
+                Some(env) => self.get_at_helper(Rc::clone(env), distance - 1, &name.lexeme),
                 None => panic!("Should have an env here"),
             }
-        }
+        };
+        value.ok_or_else(|| undefined(name))
     }
 
     fn get_at_helper(
         &self,
         env: Rc<RefCell<Environment>>,
         distance: usize,
-        name: String,
-    ) -> LoxObject {
+        name: &str,
+    ) -> Option<LoxObject> {
         if distance > 0 {
             match &env.borrow().enclosing {
                 Some(env_ref) => self.get_at_helper(Rc::clone(env_ref), distance - 1, name),
                 None => panic!("Should have an env for assigning"),
             }
         } else {
-            match env.borrow().values.get(&name) {
-                Some(x) => x.clone(),
-                None => LoxObject::None,
-            }
+            env.borrow().values.get(name).cloned()
         }
     }
 
-    pub fn assign_at(&mut self, distance: usize, name: String, value: LoxObject) {
-        if distance == 0 {
-            self.values.insert(name, value);
+    /// Like `get_at`, assigning to a name whose declaration never ran is an error.
+    pub fn assign_at(
+        &mut self,
+        distance: usize,
+        name: &Token,
+        value: LoxObject,
+    ) -> Result<(), RuntimeError> {
+        let assigned = if distance == 0 {
+            assign_existing(&mut self.values, &name.lexeme, value)
         } else {
             match &self.enclosing {
                 Some(env) => {
-                    self.assign_at_helper(Rc::clone(env), distance - 1, name, value);
+                    self.assign_at_helper(Rc::clone(env), distance - 1, &name.lexeme, value)
                 }
                 None => panic!("Should have an env here"),
             }
+        };
+        if assigned {
+            Ok(())
+        } else {
+            Err(undefined(name))
         }
     }
 
@@ -115,26 +129,44 @@ impl Environment {
         &self,
         env: Rc<RefCell<Environment>>,
         distance: usize,
-        name: String,
+        name: &str,
         value: LoxObject,
-    ) {
+    ) -> bool {
         if distance > 0 {
             match &env.borrow().enclosing {
                 Some(env_ref) => {
-                    self.assign_at_helper(Rc::clone(env_ref), distance - 1, name, value);
+                    self.assign_at_helper(Rc::clone(env_ref), distance - 1, name, value)
                 }
                 None => panic!("Should have an env for assigning"),
             }
         } else {
-            env.borrow_mut().values.insert(name, value);
+            assign_existing(&mut env.borrow_mut().values, name, value)
         }
     }
 }
 
+/// Replaces the value of `name` if it is defined in `values`.
+fn assign_existing(values: &mut HashMap<String, LoxObject>, name: &str, value: LoxObject) -> bool {
+    match values.get_mut(name) {
+        Some(slot) => {
+            *slot = value;
+            true
+        }
+        None => false,
+    }
+}
+
+fn undefined(name: &Token) -> RuntimeError {
+    RuntimeError::raise(
+        name.clone(),
+        &format!("Undefined variable '{}'.", name.lexeme),
+    )
+}
+
 impl Clone for Environment {
     fn clone(&self) -> Self {
         let enclosing = match &self.enclosing {
-            Some(env) => Some(Rc::clone(&env)),
+            Some(env) => Some(Rc::clone(env)),
             None => None,
         };
 
@@ -147,18 +179,19 @@ impl Clone for Environment {
 
 impl Display for Environment {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let enclosing = match &self.enclosing {
-            Some(env) => env.borrow().to_string(),
-            None => "None".to_string(),
-        };
-
-        write!(
-            f,
-            "{{keys: {:?}, values: {:?}, enclosing: {} }}",
-            self.values.keys(),
-            self.values.values(),
-            enclosing
-        )
+        let mut names: Vec<&String> = self.values.keys().collect();
+        names.sort();
+        let values = names
+            .into_iter()
+            .map(|name| format!("{}: {}", name, self.values[name]))
+            .collect::<Vec<_>>()
+            .join(", ");
+        write!(f, "{{{}}}", values)?;
+
+        if let Some(env) = &self.enclosing {
+            write!(f, " -> {}", env.borrow())?;
+        }
+        Ok(())
     }
 }
 
@@ -1,20 +1,180 @@
-use crate::tokens::LoxObject;
+use std::fmt::Display;
 
-#[derive(Debug)]
-pub struct RuntimeError {}
+use crate::{
+    logger,
+    tokens::{LoxObject, Span, Token},
+};
 
-#[derive(Debug)]
-pub struct Return {
-    pub value: LoxObject,
+#[derive(Debug, Clone)]
+pub struct RuntimeError {
+    pub token: Box<Token>,
+    pub line: usize,
+    pub msg: String,
+    /// Lox call stack at the time of the error, innermost frame first.
+    pub trace: Vec<String>,
+    /// The value of a `throw`, which travels as an error through function calls.
+    pub thrown: Option<Box<LoxObject>>,
+}
+
+impl RuntimeError {
+    pub fn raise(token: Token, msg: &str) -> Self {
+        RuntimeError {
+            line: token.line,
+            token: Box::new(token),
+            msg: msg.to_string(),
+            trace: Vec::new(),
+            thrown: None,
+        }
+    }
+
+    pub fn traceback(&self) -> String {
+        self.trace.join("\n")
+    }
+}
+
+impl Display for RuntimeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} [line {}]", self.msg, self.line)
+    }
 }
 
+/// Reasons for a statement to stop executing before it reaches its end.
 #[derive(Debug)]
+pub enum Unwind {
+    /// A `return` on its way out to the innermost function call.
+    Return(LoxObject),
+    /// A `break` on its way out to the innermost loop.
+    Break,
+    /// A `continue` on its way out to the innermost loop.
+    Continue,
+    /// A value thrown by the script, carried in an error to report if it is
+    /// never caught.
+    Throw(RuntimeError),
+    /// An error raised by the interpreter itself.
+    Error(RuntimeError),
+}
+
+impl From<RuntimeError> for Unwind {
+    fn from(error: RuntimeError) -> Self {
+        if error.thrown.is_some() {
+            Unwind::Throw(error)
+        } else {
+            Unwind::Error(error)
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ScannerError {
+    pub line: usize,
+    pub span: Span,
+    pub msg: String,
+}
+
+impl ScannerError {
+    pub fn raise(line: usize, span: Span, msg: &str) -> Self {
+        ScannerError {
+            line,
+            span,
+            msg: msg.to_string(),
+        }
+    }
+}
+
+impl Display for ScannerError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", logger::format_error(self.line, &self.msg))
+    }
+}
+
+#[derive(Debug, Clone)]
 pub struct ParserError {
+    pub token: Box<Token>,
+    pub line: usize,
     pub msg: String,
 }
 
 impl ParserError {
-    pub fn raise(msg: String) -> Self {
-        ParserError { msg }
+    pub fn raise(token: Token, msg: &str) -> Self {
+        ParserError {
+            line: token.line,
+            token: Box::new(token),
+            msg: msg.to_string(),
+        }
+    }
+}
+
+impl Display for ParserError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", logger::format_token_error(&self.token, &self.msg))
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ResolverError {
+    pub token: Box<Token>,
+    pub line: usize,
+    pub msg: String,
+}
+
+impl ResolverError {
+    pub fn raise(token: Token, msg: &str) -> Self {
+        ResolverError {
+            line: token.line,
+            token: Box::new(token),
+            msg: msg.to_string(),
+        }
+    }
+}
+
+impl Display for ResolverError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", logger::format_token_error(&self.token, &self.msg))
+    }
+}
+
+#[derive(Debug)]
+pub enum LoxError {
+    Scan(Vec<ScannerError>),
+    Parse(Vec<ParserError>),
+    Resolve(Vec<ResolverError>),
+    Runtime(RuntimeError),
+}
+
+impl From<RuntimeError> for LoxError {
+    fn from(error: RuntimeError) -> Self {
+        LoxError::Runtime(error)
+    }
+}
+
+impl Display for LoxError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LoxError::Scan(x) => write!(
+                f,
+                "{}",
+                x.iter()
+                    .map(|error| error.to_string())
+                    .collect::<Vec<_>>()
+                    .join("\n")
+            ),
+            LoxError::Parse(x) => write!(
+                f,
+                "{}",
+                x.iter()
+                    .map(|error| error.to_string())
+                    .collect::<Vec<_>>()
+                    .join("\n")
+            ),
+            LoxError::Resolve(x) => write!(
+                f,
+                "{}",
+                x.iter()
+                    .map(|error| error.to_string())
+                    .collect::<Vec<_>>()
+                    .join("\n")
+            ),
+            LoxError::Runtime(x) => write!(f, "{}", x),
+        }
     }
 }
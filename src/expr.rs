@@ -1,7 +1,7 @@
 use core::fmt;
 use std::fmt::Display;
 
-use crate::tokens::{LoxObject, Token};
+use crate::tokens::{LoxObject, Span, Token};
 
 pub trait ExprVisitor<T> {
     fn visit_binary_expr(&mut self, expr: BinaryExpr) -> T;
@@ -16,6 +16,11 @@ pub trait ExprVisitor<T> {
     fn visit_set_expr(&mut self, expr: SetExpr) -> T;
     fn visit_this_expr(&mut self, expr: ThisExpr) -> T;
     fn visit_super_expr(&mut self, expr: SuperExpr) -> T;
+    fn visit_interpolation_expr(&mut self, expr: InterpolationExpr) -> T;
+    fn visit_list_expr(&mut self, expr: ListExpr) -> T;
+    fn visit_map_expr(&mut self, expr: MapExpr) -> T;
+    fn visit_index_expr(&mut self, expr: IndexExpr) -> T;
+    fn visit_index_set_expr(&mut self, expr: IndexSetExpr) -> T;
 }
 
 pub trait ExprVisitorAcceptor<T> {
@@ -36,6 +41,35 @@ pub enum Expr {
     Set(SetExpr),
     This(ThisExpr),
     Super(SuperExpr),
+    Interpolation(InterpolationExpr),
+    List(ListExpr),
+    Map(MapExpr),
+    Index(IndexExpr),
+    IndexSet(IndexSetExpr),
+}
+
+impl Expr {
+    pub fn span(&self) -> Span {
+        match self {
+            Self::Binary(x) => x.span,
+            Self::Grouping(x) => x.span,
+            Self::Literal(x) => x.span,
+            Self::Unary(x) => x.span,
+            Self::Variable(x) => x.span,
+            Self::Assign(x) => x.span,
+            Self::Logical(x) => x.span,
+            Self::Call(x) => x.span,
+            Self::Get(x) => x.span,
+            Self::Set(x) => x.span,
+            Self::This(x) => x.span,
+            Self::Super(x) => x.span,
+            Self::Interpolation(x) => x.span,
+            Self::List(x) => x.span,
+            Self::Map(x) => x.span,
+            Self::Index(x) => x.span,
+            Self::IndexSet(x) => x.span,
+        }
+    }
 }
 
 impl Display for Expr {
@@ -43,15 +77,44 @@ impl Display for Expr {
         match self {
             Self::Literal(x) => write!(f, "Literal({})", x.value),
             Self::Grouping(x) => write!(f, "Group({})", x.expression),
-            Self::Binary(x) => write!(f, "Binary({} {} {})", x.left, x.operator, x.right),
-            Self::Unary(x) => write!(f, "Unary({} {})", x.operator, x.right),
-            Self::Variable(x) => write!(f, "Var({})", x.name),
-            Self::Assign(x) => write!(f, "Assign({} = {})", x.name, x.value),
-            Self::Logical(x) => write!(f, "Logical({} {} {})", x.left, x.operator, x.right),
-            Self::Get(x) => write!(f, "Get({} {})", x.object, x.name),
-            Self::Set(x) => write!(f, "Set({} {} {})", x.object, x.name, x.value),
-            Self::This(x) => write!(f, "This({})", x.name),
-            Self::Super(x) => write!(f, "Super({} {})", x.keyword, x.method),
+            Self::Binary(x) => write!(f, "Binary({} {} {})", x.left, x.operator.lexeme, x.right),
+            Self::Unary(x) => write!(f, "Unary({} {})", x.operator.lexeme, x.right),
+            Self::Variable(x) => write!(f, "Var({})", x.name.lexeme),
+            Self::Assign(x) => write!(f, "Assign({} = {})", x.name.lexeme, x.value),
+            Self::Logical(x) => write!(f, "Logical({} {} {})", x.left, x.operator.lexeme, x.right),
+            Self::Get(x) => write!(f, "Get({} {})", x.object, x.name.lexeme),
+            Self::Set(x) => write!(f, "Set({} {} = {})", x.object, x.name.lexeme, x.value),
+            Self::This(_) => write!(f, "This"),
+            Self::Super(x) => write!(f, "Super({})", x.method.lexeme),
+            Self::Interpolation(x) => write!(
+                f,
+                "Interpolation({})",
+                x.parts
+                    .iter()
+                    .map(|part| part.to_string())
+                    .collect::<Vec<_>>()
+                    .join(" ")
+            ),
+            Self::List(x) => write!(
+                f,
+                "List({})",
+                x.elements
+                    .iter()
+                    .map(|element| element.to_string())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+            Self::Map(x) => write!(
+                f,
+                "Map({})",
+                x.entries
+                    .iter()
+                    .map(|(key, value)| format!("{}: {}", key, value))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+            Self::Index(x) => write!(f, "Index({} {})", x.object, x.index),
+            Self::IndexSet(x) => write!(f, "IndexSet({} {} = {})", x.object, x.index, x.value),
             Self::Call(x) => write!(
                 f,
                 "Call({} ({}))",
@@ -71,11 +134,13 @@ pub struct BinaryExpr {
     pub left: Box<Expr>,
     pub operator: Token,
     pub right: Box<Expr>,
+    pub span: Span,
 }
 
 impl BinaryExpr {
     pub fn new(left: Expr, operator: Token, right: Expr) -> Self {
         BinaryExpr {
+            span: left.span().to(right.span()),
             left: Box::new(left),
             operator,
             right: Box::new(right),
@@ -92,12 +157,14 @@ impl<T> ExprVisitorAcceptor<T> for BinaryExpr {
 #[derive(Clone, Debug)]
 pub struct GroupingExpr {
     pub expression: Box<Expr>,
+    pub span: Span,
 }
 
 impl GroupingExpr {
-    pub fn new(expression: Expr) -> Self {
+    pub fn new(expression: Expr, span: Span) -> Self {
         GroupingExpr {
             expression: Box::new(expression),
+            span,
         }
     }
 }
@@ -111,11 +178,12 @@ impl<T> ExprVisitorAcceptor<T> for GroupingExpr {
 #[derive(Clone, Debug)]
 pub struct LiteralExpr {
     pub value: LoxObject,
+    pub span: Span,
 }
 
 impl LiteralExpr {
-    pub fn new(value: LoxObject) -> Self {
-        LiteralExpr { value }
+    pub fn new(value: LoxObject, span: Span) -> Self {
+        LiteralExpr { value, span }
     }
 }
 
@@ -129,11 +197,13 @@ impl<T> ExprVisitorAcceptor<T> for LiteralExpr {
 pub struct UnaryExpr {
     pub right: Box<Expr>,
     pub operator: Token,
+    pub span: Span,
 }
 
 impl UnaryExpr {
     pub fn new(right: Expr, operator: Token) -> Self {
         UnaryExpr {
+            span: operator.span.to(right.span()),
             right: Box::new(right),
             operator,
         }
@@ -149,6 +219,7 @@ impl<T> ExprVisitorAcceptor<T> for UnaryExpr {
 #[derive(Debug, Clone)]
 pub struct VariableExpr {
     pub name: Token,
+    pub span: Span,
 }
 
 impl fmt::Display for VariableExpr {
@@ -159,7 +230,10 @@ impl fmt::Display for VariableExpr {
 
 impl VariableExpr {
     pub fn new(name: Token) -> Self {
-        VariableExpr { name }
+        VariableExpr {
+            span: name.span,
+            name,
+        }
     }
 }
 
@@ -173,6 +247,7 @@ impl<T> ExprVisitorAcceptor<T> for VariableExpr {
 pub struct AssignExpr {
     pub name: Token,
     pub value: Box<Expr>,
+    pub span: Span,
 }
 
 impl Display for AssignExpr {
@@ -184,6 +259,7 @@ impl Display for AssignExpr {
 impl AssignExpr {
     pub fn new(name: Token, value: Expr) -> Self {
         AssignExpr {
+            span: name.span.to(value.span()),
             name,
             value: Box::new(value),
         }
@@ -201,11 +277,13 @@ pub struct LogicalExpr {
     pub left: Box<Expr>,
     pub operator: Token,
     pub right: Box<Expr>,
+    pub span: Span,
 }
 
 impl LogicalExpr {
     pub fn new(left: Expr, operator: Token, right: Expr) -> Self {
         LogicalExpr {
+            span: left.span().to(right.span()),
             left: Box::new(left),
             operator,
             right: Box::new(right),
@@ -223,11 +301,13 @@ pub struct CallExpr {
     pub callee: Box<Expr>,
     pub paren: Token,
     pub arguments: Vec<Expr>,
+    pub span: Span,
 }
 
 impl CallExpr {
     pub fn new(callee: Expr, paren: Token, arguments: Vec<Expr>) -> Self {
         CallExpr {
+            span: callee.span().to(paren.span),
             callee: Box::new(callee),
             paren,
             arguments,
@@ -244,11 +324,13 @@ impl<T> ExprVisitorAcceptor<T> for CallExpr {
 pub struct GetExpr {
     pub object: Box<Expr>,
     pub name: Token,
+    pub span: Span,
 }
 
 impl GetExpr {
     pub fn new(object: Expr, name: Token) -> Self {
         GetExpr {
+            span: object.span().to(name.span),
             object: Box::new(object),
             name,
         }
@@ -265,11 +347,13 @@ pub struct SetExpr {
     pub object: Box<Expr>,
     pub name: Token,
     pub value: Box<Expr>,
+    pub span: Span,
 }
 
 impl SetExpr {
     pub fn new(object: Expr, name: Token, value: Expr) -> Self {
         SetExpr {
+            span: object.span().to(value.span()),
             object: Box::new(object),
             name,
             value: Box::new(value),
@@ -285,11 +369,15 @@ impl<T> ExprVisitorAcceptor<T> for SetExpr {
 #[derive(Clone, Debug)]
 pub struct ThisExpr {
     pub name: Token,
+    pub span: Span,
 }
 
 impl ThisExpr {
     pub fn new(name: Token) -> Self {
-        ThisExpr { name }
+        ThisExpr {
+            span: name.span,
+            name,
+        }
     }
 }
 impl<T> ExprVisitorAcceptor<T> for ThisExpr {
@@ -308,11 +396,16 @@ impl fmt::Display for ThisExpr {
 pub struct SuperExpr {
     pub keyword: Token,
     pub method: Token,
+    pub span: Span,
 }
 
 impl SuperExpr {
     pub fn new(keyword: Token, method: Token) -> Self {
-        SuperExpr { keyword, method }
+        SuperExpr {
+            span: keyword.span.to(method.span),
+            keyword,
+            method,
+        }
     }
 }
 impl<T> ExprVisitorAcceptor<T> for SuperExpr {
@@ -326,3 +419,118 @@ impl<T> ExprVisitorAcceptor<T> for SuperExpr {
 //         write!(f, "super keyword: {}", self.keyword.lexeme)
 //     }
 // }
+
+/// A string literal with `${...}` in it, the parts are stringified and joined.
+#[derive(Clone, Debug)]
+pub struct InterpolationExpr {
+    pub parts: Vec<Expr>,
+    pub span: Span,
+}
+
+impl InterpolationExpr {
+    pub fn new(parts: Vec<Expr>, span: Span) -> Self {
+        InterpolationExpr { parts, span }
+    }
+}
+
+impl<T> ExprVisitorAcceptor<T> for InterpolationExpr {
+    fn accept(&self, visitor: &mut impl ExprVisitor<T>) -> T {
+        visitor.visit_interpolation_expr(self.clone())
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct ListExpr {
+    pub elements: Vec<Expr>,
+    pub span: Span,
+}
+
+impl ListExpr {
+    pub fn new(elements: Vec<Expr>, span: Span) -> Self {
+        ListExpr { elements, span }
+    }
+}
+
+impl<T> ExprVisitorAcceptor<T> for ListExpr {
+    fn accept(&self, visitor: &mut impl ExprVisitor<T>) -> T {
+        visitor.visit_list_expr(self.clone())
+    }
+}
+
+/// `{key: value, ...}`, the entries in source order and the brace the closing
+/// one.
+#[derive(Clone, Debug)]
+pub struct MapExpr {
+    pub entries: Vec<(Expr, Expr)>,
+    pub brace: Token,
+    pub span: Span,
+}
+
+impl MapExpr {
+    pub fn new(entries: Vec<(Expr, Expr)>, brace: Token, span: Span) -> Self {
+        MapExpr {
+            entries,
+            brace,
+            span,
+        }
+    }
+}
+
+impl<T> ExprVisitorAcceptor<T> for MapExpr {
+    fn accept(&self, visitor: &mut impl ExprVisitor<T>) -> T {
+        visitor.visit_map_expr(self.clone())
+    }
+}
+
+/// `object[index]`, the bracket is the closing one.
+#[derive(Clone, Debug)]
+pub struct IndexExpr {
+    pub object: Box<Expr>,
+    pub bracket: Token,
+    pub index: Box<Expr>,
+    pub span: Span,
+}
+
+impl IndexExpr {
+    pub fn new(object: Expr, bracket: Token, index: Expr) -> Self {
+        IndexExpr {
+            span: object.span().to(bracket.span),
+            object: Box::new(object),
+            bracket,
+            index: Box::new(index),
+        }
+    }
+}
+
+impl<T> ExprVisitorAcceptor<T> for IndexExpr {
+    fn accept(&self, visitor: &mut impl ExprVisitor<T>) -> T {
+        visitor.visit_index_expr(self.clone())
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct IndexSetExpr {
+    pub object: Box<Expr>,
+    pub bracket: Token,
+    pub index: Box<Expr>,
+    pub value: Box<Expr>,
+    pub span: Span,
+}
+
+impl IndexSetExpr {
+    pub fn new(object: Expr, bracket: Token, index: Expr, value: Expr) -> Self {
+        IndexSetExpr {
+            span: object.span().to(value.span()),
+            object: Box::new(object),
+            bracket,
+            index: Box::new(index),
+            value: Box::new(value),
+        }
+    }
+}
+
+impl<T> ExprVisitorAcceptor<T> for IndexSetExpr {
+    fn accept(&self, visitor: &mut impl ExprVisitor<T>) -> T {
+        visitor.visit_index_set_expr(self.clone())
+    }
+}
@@ -0,0 +1,33 @@
+use crate::{compile, exceptions::LoxError, parser::Parser, scanner::Scanner};
+
+/// Lists the tokens of `source`, one per line.
+pub fn tokens(source: &str) -> Result<String, LoxError> {
+    let tokens = Scanner::new(source).scan_tokens().map_err(LoxError::Scan)?;
+    Ok(tokens.iter().map(|token| format!("{}\n", token)).collect())
+}
+
+/// Lists the top-level statements of `source`, one per line.
+pub fn ast(source: &str) -> Result<String, LoxError> {
+    let tokens = Scanner::new(source).scan_tokens().map_err(LoxError::Scan)?;
+    let statements = Parser::new(tokens).parse().map_err(LoxError::Parse)?;
+    Ok(statements
+        .iter()
+        .map(|statement| format!("{}\n", statement))
+        .collect())
+}
+
+/// Lists every variable reference the resolver bound to a local scope, along with
+/// how many scopes up from the reference the variable lives.
+pub fn resolved(source: &str) -> Result<String, LoxError> {
+    let tokens = Scanner::new(source).scan_tokens().map_err(LoxError::Scan)?;
+    let (_, interpreter) = compile(source)?;
+    Ok(tokens
+        .iter()
+        .filter_map(|token| {
+            interpreter
+                .locals
+                .get(&token.to_string())
+                .map(|depth| format!("{} {} {} {}\n", token.line, token.span, token.lexeme, depth))
+        })
+        .collect())
+}
@@ -2,6 +2,7 @@ use std::{cell::RefCell, collections::HashMap, fmt::Display, rc::Rc};
 
 use crate::{
     class::LoxClass,
+    exceptions::RuntimeError,
     tokens::{LoxObject, Token},
 };
 
@@ -19,19 +20,20 @@ impl LoxInstance {
         }
     }
 
-    pub fn get(&self, name: Token) -> LoxObject {
+    pub fn get(&self, name: Token) -> Result<LoxObject, RuntimeError> {
         let fields = self.fields.borrow();
         if let Some(x) = fields.get(&name.lexeme) {
-            return x.clone();
+            return Ok(x.clone());
         };
 
         if let Some(method) = self.class.find_methods(&name.lexeme) {
             let method = method.bind(self.clone());
             let method = LoxObject::FunCall(Box::new(method));
-            return method;
+            return Ok(method);
         }
 
-        panic!("Undefined property {}.", name.lexeme);
+        let msg = format!("Undefined property '{}'.", name.lexeme);
+        Err(RuntimeError::raise(name, &msg))
     }
 
     pub fn set(&mut self, name: Token, value: LoxObject) {
@@ -1,35 +1,117 @@
-use std::collections::HashMap;
+use std::{
+    cell::RefCell,
+    collections::HashMap,
+    rc::Rc,
+    time::{SystemTime, UNIX_EPOCH},
+};
 
 use crate::{
-    callable::{LoxCallable, LoxFunction},
+    bigint::BigInt,
+    callable::{Arity, LoxCallable, LoxFunction, NativeFunction},
     class::LoxClass,
     environment::Environment,
-    exceptions::Return,
+    exceptions::{RuntimeError, Unwind},
     expr::{Expr, ExprVisitor, ExprVisitorAcceptor},
+    instance::LoxInstance,
+    map::LoxMap,
+    methods,
     stmt::{Stmt, StmtVisitor, StmtVisitorAcceptor},
-    tokens::{LoxObject, Token, TokenType},
+    tokens::{LoxObject, Span, Token, TokenType},
 };
 
+/// Most Lox calls that can be active at once. Deeper recursion is a runtime error
+/// rather than a Rust stack overflow, as long as the thread running the script
+/// has room for this many calls, see `STACK_SIZE` in main.rs.
+pub const MAX_FRAMES: usize = 1000;
+
+/// A Lox function call that has not returned yet.
+#[derive(Clone, Debug)]
+pub struct CallFrame {
+    pub name: String,
+    /// Closing paren of the call site in the caller.
+    pub paren: Token,
+}
+
 #[derive(Clone)]
 pub struct Interpreter {
-    pub globals: Environment,
-    pub environment: Environment,
+    /// Outermost scope, holding the natives. Enclosing the top-level scope.
+    pub globals: Rc<RefCell<Environment>>,
+    /// Scope of the script itself, where the variables the resolver left
+    /// unresolved live.
+    pub top_level: Rc<RefCell<Environment>>,
+    /// Innermost scope of the code being run. Scopes are shared with the
+    /// closures created in them, so those see later changes to them.
+    pub environment: Rc<RefCell<Environment>>,
     pub locals: HashMap<String, usize>,
     pub output: String,
+    pub frames: Vec<CallFrame>,
 }
 
 impl Interpreter {
     pub fn new() -> Self {
-        let globals = Environment::new();
-        Interpreter {
-            globals: globals.clone(),
-            environment: Environment::new_with_enclosing(globals),
+        let globals = Rc::new(RefCell::new(Environment::new()));
+        let top_level = Rc::new(RefCell::new(Environment::new_with_enclosing_rc(Rc::clone(
+            &globals,
+        ))));
+        let mut interpreter = Interpreter {
+            environment: Rc::clone(&top_level),
+            top_level,
+            globals,
             locals: HashMap::new(),
             output: String::new(),
-        }
+            frames: Vec::new(),
+        };
+        interpreter.define_native("clock", Arity::Exact(0), |_, _| {
+            let now = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map_err(|error| error.to_string())?;
+            Ok(LoxObject::Number(now.as_millis() as f64))
+        });
+        interpreter
+    }
+
+    /// Exposes a Rust closure to scripts as the global function `name`. The
+    /// arguments are checked against `arity` before `function` is called, and an
+    /// error it returns is raised at the call site.
+    pub fn define_native(
+        &mut self,
+        name: &str,
+        arity: Arity,
+        function: impl Fn(&mut Interpreter, Vec<LoxObject>) -> Result<LoxObject, String> + 'static,
+    ) {
+        let native = NativeFunction {
+            name: name.to_string(),
+            arity,
+            function: Rc::new(function),
+        };
+        self.globals
+            .borrow_mut()
+            .define(name.to_string(), LoxObject::Native(Rc::new(native)));
+    }
+
+    /// A runtime error at the innermost call site, for natives to report with.
+    /// A native called from Rust rather than from a script has no call site, so
+    /// its errors are reported at line 0.
+    pub fn error(&self, msg: &str) -> RuntimeError {
+        let paren = match self.frames.last() {
+            Some(frame) => frame.paren.clone(),
+            None => Token::new(
+                TokenType::Eof,
+                String::new(),
+                LoxObject::None,
+                0,
+                Span::default(),
+            ),
+        };
+        RuntimeError::raise(paren, msg)
     }
 
-    fn evaluate_expr(&mut self, expr: Expr) -> LoxObject {
+    /// Defines `name` in the top-level scope of the program about to be run.
+    pub fn define_global(&mut self, name: &str, value: LoxObject) {
+        self.top_level.borrow_mut().define(name.to_string(), value);
+    }
+
+    fn evaluate_expr(&mut self, expr: Expr) -> Result<LoxObject, RuntimeError> {
         match expr {
             Expr::Binary(x) => self.evaluate(x),
             Expr::Grouping(x) => self.evaluate(x),
@@ -43,10 +125,15 @@ impl Interpreter {
             Expr::Set(x) => self.evaluate(x),
             Expr::This(x) => self.evaluate(x),
             Expr::Super(x) => self.evaluate(x),
+            Expr::Interpolation(x) => self.evaluate(x),
+            Expr::List(x) => self.evaluate(x),
+            Expr::Map(x) => self.evaluate(x),
+            Expr::Index(x) => self.evaluate(x),
+            Expr::IndexSet(x) => self.evaluate(x),
         }
     }
 
-    fn execute_stmt(&mut self, statement: Stmt) -> Result<LoxObject, Return> {
+    fn execute_stmt(&mut self, statement: Stmt) -> Result<LoxObject, Unwind> {
         match statement {
             Stmt::Expression(x) => self.execute(x),
             Stmt::Print(x) => self.execute(x),
@@ -57,60 +144,106 @@ impl Interpreter {
             Stmt::Fun(x) => self.execute(x),
             Stmt::Return(x) => self.execute(x),
             Stmt::Class(x) => self.execute(x),
+            Stmt::Break(x) => self.execute(x),
+            Stmt::Continue(x) => self.execute(x),
+            Stmt::Throw(x) => self.execute(x),
+            Stmt::Try(x) => self.execute(x),
         }
     }
 
-    fn is_truthy(&self, object: LoxObject) -> LoxObject {
+    fn is_truthy(&self, object: &LoxObject) -> bool {
         match object {
-            LoxObject::None => LoxObject::Bool(false),
-            LoxObject::Bool(b) => LoxObject::Bool(b),
-            _ => LoxObject::Bool(true),
+            LoxObject::None => false,
+            LoxObject::Bool(b) => *b,
+            _ => true,
         }
     }
-    pub fn interpret(mut self, statements: Vec<Stmt>) -> String {
-        for statement in statements {
-            let _ = self.execute_stmt(statement);
+
+    fn check_comparable_operands(
+        &self,
+        operator: &Token,
+        left: &LoxObject,
+        right: &LoxObject,
+    ) -> Result<(), RuntimeError> {
+        match (left, right) {
+            (LoxObject::String(_), LoxObject::String(_)) => Ok(()),
+            _ if left.as_float().is_some() && right.as_float().is_some() => Ok(()),
+            _ => Err(RuntimeError::raise(
+                operator.clone(),
+                "Operands must be two numbers or two strings.",
+            )),
         }
-        self.output
     }
 
-    pub fn resolve(&mut self, expr: Token, depth: usize) {
-        self.locals.insert(expr.to_string(), depth);
+    pub fn interpret(&mut self, statements: Vec<Stmt>) -> Result<(), RuntimeError> {
+        for statement in statements {
+            self.execute_statement(statement)?;
+        }
+        Ok(())
     }
 
-    pub fn execute_block(
-        &mut self,
-        statements: Vec<Stmt>,
-        environment: Option<Environment>,
-    ) -> Result<(), Return> {
-        match &environment {
-            Some(env) => self.environment = Environment::new_with_enclosing(env.clone()),
-            None => self.environment = Environment::new_with_enclosing(self.environment.clone()),
-        };
-        let mut response = Ok(());
-        for stmt in statements {
-            if let Err(x) = self.execute_stmt(stmt) {
-                response = Err(x);
-                break;
+    /// Executes a single top-level statement, returning the value of expression
+    /// statements and `nil` for everything else.
+    pub fn execute_statement(&mut self, statement: Stmt) -> Result<LoxObject, RuntimeError> {
+        match self.execute_stmt(statement) {
+            Ok(value) => Ok(value),
+            Err(Unwind::Return(_)) => Ok(LoxObject::None),
+            Err(Unwind::Break | Unwind::Continue) => {
+                unreachable!("the resolver keeps loop jumps inside loops")
             }
-        }
-        match self.environment.clone().enclosing {
-            Some(x) => {
-                let env = x.borrow();
-                self.environment = env.clone();
+            Err(Unwind::Throw(mut error) | Unwind::Error(mut error)) => {
+                if error.trace.is_empty() {
+                    error.trace = self.traceback(error.line);
+                }
+                Err(error)
             }
-            None => panic!("Should always have enclosing env"),
         }
-        response
+    }
+
+    /// Writes `value` to stdout and records it in `output`.
+    pub fn print(&mut self, value: &LoxObject) {
+        self.output.push_str(&format!("{}\n", value));
+        println!("{}", value);
+    }
+
+    /// Formats the current call stack for an error raised at `line`.
+    fn traceback(&self, line: usize) -> Vec<String> {
+        let mut trace = Vec::new();
+        let mut line = line;
+        for frame in self.frames.iter().rev() {
+            trace.push(format!("at {}() line {}", frame.name, line));
+            line = frame.paren.line;
+        }
+        trace.push(format!("at <script> line {}", line));
+        trace
+    }
+
+    pub fn resolve(&mut self, expr: Token, depth: usize) {
+        self.locals.insert(expr.to_string(), depth);
+    }
+
+    /// Runs `statements` in a new scope nested in the current one.
+    pub fn execute_block(&mut self, statements: Vec<Stmt>) -> Result<(), Unwind> {
+        let environment = self.nested_scope();
+        self.execute_in(statements, environment)
     }
 
     pub fn execute_fun(
         &mut self,
         statements: Vec<Stmt>,
         environment: Environment,
-    ) -> Result<(), Return> {
-        let previous = self.environment.clone();
-        self.environment = environment;
+    ) -> Result<(), Unwind> {
+        self.execute_in(statements, Rc::new(RefCell::new(environment)))
+    }
+
+    /// Runs `statements` with `environment` as the innermost scope, then puts
+    /// back the current one.
+    fn execute_in(
+        &mut self,
+        statements: Vec<Stmt>,
+        environment: Rc<RefCell<Environment>>,
+    ) -> Result<(), Unwind> {
+        let previous = std::mem::replace(&mut self.environment, environment);
         let mut response = Ok(());
         for stmt in statements {
             if let Err(x) = self.execute_stmt(stmt) {
@@ -122,166 +255,366 @@ impl Interpreter {
         response
     }
 
-    fn lookup_variable(&mut self, expr: crate::expr::VariableExpr) -> LoxObject {
+    fn nested_scope(&self) -> Rc<RefCell<Environment>> {
+        Rc::new(RefCell::new(Environment::new_with_enclosing_rc(Rc::clone(
+            &self.environment,
+        ))))
+    }
+
+    /// The value a `catch` clause receives for `error`: what was thrown, or an
+    /// `Error` instance with the `message` and `line` of an interpreter error.
+    fn exception(&self, error: RuntimeError) -> LoxObject {
+        if let Some(value) = error.thrown {
+            return *value;
+        }
+        let instance = LoxInstance::new(LoxClass::new("Error".to_string(), None, HashMap::new()));
+        let mut fields = instance.fields.borrow_mut();
+        fields.insert("message".to_string(), LoxObject::String(error.msg));
+        fields.insert("line".to_string(), LoxObject::Int(error.line as i64));
+        drop(fields);
+        LoxObject::Instance(instance)
+    }
+
+    fn lookup_variable(
+        &mut self,
+        expr: crate::expr::VariableExpr,
+    ) -> Result<LoxObject, RuntimeError> {
         match self.locals.get(&expr.name.to_string()) {
-            Some(x) => self.environment.get_at(*x, expr.name.lexeme),
-            None => self.environment.get(expr.name.lexeme).unwrap(),
+            Some(x) => self.environment.borrow().get_at(*x, &expr.name),
+            None => self.top_level.borrow().get(&expr.name),
         }
     }
 
-    fn lookup_this(&mut self, expr: crate::expr::ThisExpr) -> LoxObject {
+    fn lookup_this(&mut self, expr: crate::expr::ThisExpr) -> Result<LoxObject, RuntimeError> {
         match self.locals.get(&expr.name.to_string()) {
-            Some(x) => self.environment.get_at(*x, "this".to_string()),
-            None => self.environment.get("this".to_string()).unwrap(),
+            Some(x) => self.environment.borrow().get_at(*x, &expr.name),
+            None => self.top_level.borrow().get(&expr.name),
         }
     }
 }
 
+impl Default for Interpreter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl Interpreter
 where
-    Interpreter: ExprVisitor<LoxObject>,
+    Interpreter: ExprVisitor<Result<LoxObject, RuntimeError>>,
 {
-    pub fn evaluate<A: ExprVisitorAcceptor<LoxObject>>(&mut self, expr: A) -> LoxObject {
+    pub fn evaluate<A: ExprVisitorAcceptor<Result<LoxObject, RuntimeError>>>(
+        &mut self,
+        expr: A,
+    ) -> Result<LoxObject, RuntimeError> {
         expr.accept(self)
     }
 }
 
-impl ExprVisitor<LoxObject> for Interpreter {
-    fn visit_assign_expr(&mut self, expr: crate::expr::AssignExpr) -> LoxObject {
-        let value = self.evaluate_expr(*expr.value.clone());
+impl ExprVisitor<Result<LoxObject, RuntimeError>> for Interpreter {
+    fn visit_assign_expr(
+        &mut self,
+        expr: crate::expr::AssignExpr,
+    ) -> Result<LoxObject, RuntimeError> {
+        let value = self.evaluate_expr(*expr.value.clone())?;
 
         match self.locals.get(&expr.name.to_string()) {
             Some(distance) => {
                 self.environment
-                    .assign_at(*distance, expr.name.lexeme, value.clone())
+                    .borrow_mut()
+                    .assign_at(*distance, &expr.name, value.clone())?
             }
-            None => self.environment.assign(expr.name.lexeme, value.clone()),
+            None => self
+                .top_level
+                .borrow_mut()
+                .assign(&expr.name, value.clone())?,
         }
 
-        value
+        Ok(value)
     }
 
-    fn visit_literal_expr(&mut self, expr: crate::expr::LiteralExpr) -> LoxObject {
-        expr.value
+    fn visit_literal_expr(
+        &mut self,
+        expr: crate::expr::LiteralExpr,
+    ) -> Result<LoxObject, RuntimeError> {
+        Ok(expr.value)
     }
 
-    fn visit_variable_expr(&mut self, expr: crate::expr::VariableExpr) -> LoxObject {
+    fn visit_variable_expr(
+        &mut self,
+        expr: crate::expr::VariableExpr,
+    ) -> Result<LoxObject, RuntimeError> {
         self.lookup_variable(expr)
     }
 
-    fn visit_grouping_expr(&mut self, expr: crate::expr::GroupingExpr) -> LoxObject {
+    fn visit_grouping_expr(
+        &mut self,
+        expr: crate::expr::GroupingExpr,
+    ) -> Result<LoxObject, RuntimeError> {
         self.evaluate_expr(*expr.expression)
     }
 
-    fn visit_unary_expr(&mut self, expr: crate::expr::UnaryExpr) -> LoxObject {
-        let right = self.evaluate_expr(*expr.right);
+    fn visit_unary_expr(
+        &mut self,
+        expr: crate::expr::UnaryExpr,
+    ) -> Result<LoxObject, RuntimeError> {
+        let right = self.evaluate_expr(*expr.right)?;
 
         match expr.operator.token_type {
-            TokenType::Bang => match self.is_truthy(right) {
-                LoxObject::Bool(true) => LoxObject::Bool(false),
-                LoxObject::Bool(false) => LoxObject::Bool(true),
-                _ => panic!("Not possible"),
-            },
+            TokenType::Bang => Ok(LoxObject::Bool(!self.is_truthy(&right))),
             TokenType::Minus => match right {
-                LoxObject::Number(n) => LoxObject::Number(-1.0 * n),
-                _ => panic!(),
+                LoxObject::Number(n) => Ok(LoxObject::Number(-n)),
+                LoxObject::Int(n) => match n.checked_neg() {
+                    Some(n) => Ok(LoxObject::Int(n)),
+                    None => Ok(LoxObject::BigInt(BigInt::from(n).neg())),
+                },
+                LoxObject::BigInt(n) => Ok(LoxObject::integer(n.neg())),
+                _ => Err(RuntimeError::raise(
+                    expr.operator,
+                    "Operand must be a number.",
+                )),
             },
-            _ => panic!(),
+            _ => unreachable!(),
         }
     }
 
-    fn visit_binary_expr(&mut self, expr: crate::expr::BinaryExpr) -> LoxObject {
-        let left = self.evaluate_expr(*expr.left);
-        let right = self.evaluate_expr(*expr.right);
-        match expr.operator.token_type {
-            TokenType::Greater => LoxObject::Bool(left > right),
-            TokenType::GreaterEqual => LoxObject::Bool(left >= right),
-            TokenType::Less => LoxObject::Bool(left < right),
-            TokenType::LessEqual => LoxObject::Bool(left <= right),
-            TokenType::Minus => left - right,
-            TokenType::Plus => left + right,
-            TokenType::Slash => left / right,
-            TokenType::Star => left * right,
-            TokenType::BangEqual => LoxObject::Bool(left != right),
-            TokenType::EqualEqual => LoxObject::Bool(left == right),
-            _ => panic!(),
-        }
-    }
-
-    fn visit_logical_expr(&mut self, expr: crate::expr::LogicalExpr) -> LoxObject {
-        let left = self.evaluate_expr(*expr.left);
+    fn visit_binary_expr(
+        &mut self,
+        expr: crate::expr::BinaryExpr,
+    ) -> Result<LoxObject, RuntimeError> {
+        let left = self.evaluate_expr(*expr.left)?;
+        let right = self.evaluate_expr(*expr.right)?;
+        let operator = expr.operator;
+        match operator.token_type {
+            TokenType::Greater => {
+                self.check_comparable_operands(&operator, &left, &right)?;
+                Ok(LoxObject::Bool(left > right))
+            }
+            TokenType::GreaterEqual => {
+                self.check_comparable_operands(&operator, &left, &right)?;
+                Ok(LoxObject::Bool(left >= right))
+            }
+            TokenType::Less => {
+                self.check_comparable_operands(&operator, &left, &right)?;
+                Ok(LoxObject::Bool(left < right))
+            }
+            TokenType::LessEqual => {
+                self.check_comparable_operands(&operator, &left, &right)?;
+                Ok(LoxObject::Bool(left <= right))
+            }
+            TokenType::Minus => (left - right).map_err(|msg| RuntimeError::raise(operator, &msg)),
+            TokenType::Plus => (left + right).map_err(|msg| RuntimeError::raise(operator, &msg)),
+            TokenType::Slash => (left / right).map_err(|msg| RuntimeError::raise(operator, &msg)),
+            TokenType::Star => (left * right).map_err(|msg| RuntimeError::raise(operator, &msg)),
+            TokenType::Percent => (left % right).map_err(|msg| RuntimeError::raise(operator, &msg)),
+            TokenType::BangEqual => Ok(LoxObject::Bool(left != right)),
+            TokenType::EqualEqual => Ok(LoxObject::Bool(left == right)),
+            _ => unreachable!(),
+        }
+    }
+
+    fn visit_logical_expr(
+        &mut self,
+        expr: crate::expr::LogicalExpr,
+    ) -> Result<LoxObject, RuntimeError> {
+        let left = self.evaluate_expr(*expr.left)?;
         if expr.operator.token_type == TokenType::Or {
-            if let LoxObject::Bool(true) = self.is_truthy(left.clone()) {
-                return left;
+            if self.is_truthy(&left) {
+                return Ok(left);
             }
-        } else if let LoxObject::Bool(false) = self.is_truthy(left.clone()) {
-            return left;
+        } else if !self.is_truthy(&left) {
+            return Ok(left);
         }
         self.evaluate_expr(*expr.right)
     }
 
-    fn visit_call_expr(&mut self, expr: crate::expr::CallExpr) -> LoxObject {
-        let callee = self.evaluate_expr(*expr.callee.clone());
+    fn visit_call_expr(&mut self, expr: crate::expr::CallExpr) -> Result<LoxObject, RuntimeError> {
+        let callee = self.evaluate_expr(*expr.callee)?;
 
         let mut arguments = Vec::new();
         for argument in expr.arguments {
-            arguments.push(self.evaluate_expr(argument));
+            arguments.push(self.evaluate_expr(argument)?);
         }
 
-        if let LoxObject::FunCall(function) = callee {
-            if arguments.len() != function.arity() {
-                panic!("Unexpected number of arguments received");
+        let function: Rc<dyn LoxCallable> = match callee {
+            LoxObject::FunCall(function) => Rc::new(*function),
+            LoxObject::Class(class) => Rc::new(class),
+            LoxObject::Native(native) => native,
+            _ => {
+                return Err(RuntimeError::raise(
+                    expr.paren,
+                    "Can only call functions and classes.",
+                ))
             }
-            function.call(self, arguments)
-        } else if let LoxObject::Class(class) = callee {
-            class.call(self, arguments)
-        } else {
-            panic!("Expression not of type LoxCallable")
+        };
+        let arity = function.arity();
+        if !arity.accepts(arguments.len()) {
+            return Err(RuntimeError::raise(
+                expr.paren,
+                &format!(
+                    "Expected {} {} but got {}.",
+                    arity,
+                    arity.noun(),
+                    arguments.len()
+                ),
+            ));
         }
+
+        if self.frames.len() >= MAX_FRAMES {
+            return Err(RuntimeError::raise(expr.paren, "Stack overflow."));
+        }
+        self.frames.push(CallFrame {
+            name: function.name(),
+            paren: expr.paren,
+        });
+        let result = function.call(self, arguments).map_err(|mut error| {
+            if error.trace.is_empty() {
+                error.trace = self.traceback(error.line);
+            }
+            error
+        });
+        self.frames.pop();
+        result
     }
 
-    fn visit_get_expr(&mut self, expr: crate::expr::GetExpr) -> LoxObject {
-        let object = self.evaluate_expr(*expr.object);
+    fn visit_get_expr(&mut self, expr: crate::expr::GetExpr) -> Result<LoxObject, RuntimeError> {
+        let object = self.evaluate_expr(*expr.object)?;
 
-        if let LoxObject::Instance(instance) = object {
-            return instance.get(expr.name);
+        match object {
+            LoxObject::Instance(instance) => instance.get(expr.name),
+            // Other values only have built-in methods
+            object => methods::get(object, expr.name),
         }
-
-        panic!("Only instances have properties.")
     }
 
-    fn visit_set_expr(&mut self, expr: crate::expr::SetExpr) -> LoxObject {
-        let object = self.evaluate_expr(*expr.object);
+    fn visit_set_expr(&mut self, expr: crate::expr::SetExpr) -> Result<LoxObject, RuntimeError> {
+        let object = self.evaluate_expr(*expr.object)?;
 
         if let LoxObject::Instance(mut instance) = object {
-            let value = self.evaluate_expr(*expr.value);
+            let value = self.evaluate_expr(*expr.value)?;
             instance.set(expr.name, value.clone());
-            value
+            Ok(value)
         } else {
-            panic!("Only instance have fields!");
+            Err(RuntimeError::raise(
+                expr.name,
+                "Only instances have fields.",
+            ))
         }
     }
 
-    fn visit_this_expr(&mut self, expr: crate::expr::ThisExpr) -> LoxObject {
+    fn visit_this_expr(&mut self, expr: crate::expr::ThisExpr) -> Result<LoxObject, RuntimeError> {
         self.lookup_this(expr)
     }
 
-    fn visit_super_expr(&mut self, expr: crate::expr::SuperExpr) -> LoxObject {
+    fn visit_super_expr(
+        &mut self,
+        expr: crate::expr::SuperExpr,
+    ) -> Result<LoxObject, RuntimeError> {
         if let Some(distance) = self.locals.get(&expr.keyword.to_string()) {
-            let superclass = self.environment.get_at(*distance, "super".to_string());
-
-            let object = self.environment.get_at(distance - 1, "this".to_string());
+            let environment = self.environment.borrow();
+            let superclass = environment.get_at(*distance, &expr.keyword)?;
+            let this = Token {
+                lexeme: "this".to_string(),
+                ..expr.keyword.clone()
+            };
+            let object = environment.get_at(distance - 1, &this)?;
             if let LoxObject::Class(func) = superclass {
                 if let Some(method) = func.find_methods(&expr.method.lexeme) {
                     if let LoxObject::Instance(instance) = object {
-                        return LoxObject::FunCall(Box::new(method.bind(instance)));
+                        return Ok(LoxObject::FunCall(Box::new(method.bind(instance))));
                     }
                 } else {
-                    panic!("Undefined property '{}'", expr.keyword.lexeme);
+                    let msg = format!("Undefined property '{}'.", expr.method.lexeme);
+                    return Err(RuntimeError::raise(expr.method, &msg));
                 }
             }
         }
-        LoxObject::None
+        Ok(LoxObject::None)
+    }
+
+    fn visit_interpolation_expr(
+        &mut self,
+        expr: crate::expr::InterpolationExpr,
+    ) -> Result<LoxObject, RuntimeError> {
+        let mut text = String::new();
+        for part in expr.parts {
+            text.push_str(&self.evaluate_expr(part)?.to_string());
+        }
+        Ok(LoxObject::String(text))
+    }
+
+    fn visit_list_expr(&mut self, expr: crate::expr::ListExpr) -> Result<LoxObject, RuntimeError> {
+        let mut elements = Vec::new();
+        for element in expr.elements {
+            elements.push(self.evaluate_expr(element)?);
+        }
+        Ok(LoxObject::List(Rc::new(RefCell::new(elements))))
+    }
+
+    fn visit_map_expr(&mut self, expr: crate::expr::MapExpr) -> Result<LoxObject, RuntimeError> {
+        let mut map = LoxMap::new();
+        for (key, value) in expr.entries {
+            let key = self.evaluate_expr(key)?;
+            let value = self.evaluate_expr(value)?;
+            map.insert(key, value)
+                .map_err(|msg| RuntimeError::raise(expr.brace.clone(), &msg))?;
+        }
+        Ok(LoxObject::Map(Rc::new(RefCell::new(map))))
+    }
+
+    fn visit_index_expr(
+        &mut self,
+        expr: crate::expr::IndexExpr,
+    ) -> Result<LoxObject, RuntimeError> {
+        let object = self.evaluate_expr(*expr.object)?;
+        let index = self.evaluate_expr(*expr.index)?;
+        match object {
+            LoxObject::List(list) => {
+                let list = list.borrow();
+                methods::list_index(&index, list.len(), false)
+                    .map(|index| list[index].clone())
+                    .map_err(|msg| RuntimeError::raise(expr.bracket, &msg))
+            }
+            LoxObject::Map(map) => match map.borrow().get(&index) {
+                Some(value) => Ok(value.clone()),
+                None => {
+                    let msg = format!("Undefined key '{}'.", index);
+                    Err(RuntimeError::raise(expr.bracket, &msg))
+                }
+            },
+            _ => Err(RuntimeError::raise(
+                expr.bracket,
+                "Only lists and maps can be indexed.",
+            )),
+        }
+    }
+
+    fn visit_index_set_expr(
+        &mut self,
+        expr: crate::expr::IndexSetExpr,
+    ) -> Result<LoxObject, RuntimeError> {
+        let object = self.evaluate_expr(*expr.object)?;
+        let index = self.evaluate_expr(*expr.index)?;
+        let value = self.evaluate_expr(*expr.value)?;
+        match object {
+            LoxObject::List(list) => {
+                let mut list = list.borrow_mut();
+                let index = methods::list_index(&index, list.len(), false)
+                    .map_err(|msg| RuntimeError::raise(expr.bracket, &msg))?;
+                list[index] = value.clone();
+                Ok(value)
+            }
+            LoxObject::Map(map) => {
+                map.borrow_mut()
+                    .insert(index, value.clone())
+                    .map_err(|msg| RuntimeError::raise(expr.bracket, &msg))?;
+                Ok(value)
+            }
+            _ => Err(RuntimeError::raise(
+                expr.bracket,
+                "Only lists and maps can be indexed.",
+            )),
+        }
     }
 }
 
@@ -289,7 +622,7 @@ impl Interpreter
 where
     Interpreter: StmtVisitor<LoxObject>,
 {
-    fn execute<A: StmtVisitorAcceptor<LoxObject>>(&mut self, stmt: A) -> Result<LoxObject, Return> {
+    fn execute<A: StmtVisitorAcceptor<LoxObject>>(&mut self, stmt: A) -> Result<LoxObject, Unwind> {
         stmt.accept(self)
     }
 }
@@ -298,97 +631,109 @@ impl StmtVisitor<LoxObject> for Interpreter {
     fn visit_expression_stmt(
         &mut self,
         stmt: crate::stmt::ExpressionStmt,
-    ) -> Result<LoxObject, Return> {
-        Ok(self.evaluate_expr(stmt.expression))
+    ) -> Result<LoxObject, Unwind> {
+        Ok(self.evaluate_expr(stmt.expression)?)
     }
 
-    fn visit_print_stmt(&mut self, stmt: crate::stmt::PrintStmt) -> Result<LoxObject, Return> {
-        let value = self.evaluate_expr(stmt.expression);
-        self.output.push_str(&format!("{}\n", value));
-        println!("{}", value);
+    fn visit_print_stmt(&mut self, stmt: crate::stmt::PrintStmt) -> Result<LoxObject, Unwind> {
+        let value = self.evaluate_expr(stmt.expression)?;
+        self.print(&value);
         Ok(LoxObject::None)
     }
 
-    fn visit_var_stmt(&mut self, stmt: crate::stmt::VarStmt) -> Result<LoxObject, Return> {
+    fn visit_var_stmt(&mut self, stmt: crate::stmt::VarStmt) -> Result<LoxObject, Unwind> {
         let value = match stmt.initializer {
-            Some(f) => self.evaluate_expr(f),
+            Some(f) => self.evaluate_expr(f)?,
             None => LoxObject::None,
         };
 
-        self.environment.define(stmt.name.lexeme, value);
+        self.environment
+            .borrow_mut()
+            .define(stmt.name.lexeme, value);
         Ok(LoxObject::None)
     }
 
-    fn visit_block_stmt(&mut self, stmt: crate::stmt::BlockStmt) -> Result<LoxObject, Return> {
-        self.execute_block(stmt.statements, None)?;
+    fn visit_block_stmt(&mut self, stmt: crate::stmt::BlockStmt) -> Result<LoxObject, Unwind> {
+        self.execute_block(stmt.statements)?;
         Ok(LoxObject::None)
     }
 
-    fn visit_if_stmt(&mut self, stmt: crate::stmt::IfStmt) -> Result<LoxObject, Return> {
-        let value = self.evaluate_expr(stmt.condition);
-        match self.is_truthy(value) {
-            LoxObject::Bool(true) => self.execute_stmt(*stmt.then_branch)?,
-            LoxObject::Bool(false) => {
-                if let Some(s) = *stmt.else_branch {
-                    self.execute_stmt(s)?
-                } else {
-                    LoxObject::None
-                }
-            }
-            _ => panic!(),
-        };
+    fn visit_if_stmt(&mut self, stmt: crate::stmt::IfStmt) -> Result<LoxObject, Unwind> {
+        let value = self.evaluate_expr(stmt.condition)?;
+        if self.is_truthy(&value) {
+            self.execute_stmt(*stmt.then_branch)?;
+        } else if let Some(s) = *stmt.else_branch {
+            self.execute_stmt(s)?;
+        }
         Ok(LoxObject::None)
     }
 
-    fn visit_while_stmt(&mut self, stmt: crate::stmt::WhileStmt) -> Result<LoxObject, Return> {
+    fn visit_while_stmt(&mut self, stmt: crate::stmt::WhileStmt) -> Result<LoxObject, Unwind> {
         loop {
-            let value = self.evaluate_expr(stmt.condition.clone());
-            match self.is_truthy(value) {
-                LoxObject::Bool(true) => self.execute_stmt(*stmt.body.clone()).unwrap(),
-                LoxObject::Bool(false) => break Ok(LoxObject::None),
-                _ => panic!(),
-            };
+            let value = self.evaluate_expr(stmt.condition.clone())?;
+            if !self.is_truthy(&value) {
+                break Ok(LoxObject::None);
+            }
+            match self.execute_stmt(*stmt.body.clone()) {
+                Ok(_) | Err(Unwind::Continue) => (),
+                Err(Unwind::Break) => break Ok(LoxObject::None),
+                Err(unwind) => break Err(unwind),
+            }
+            if let Some(increment) = &stmt.increment {
+                self.evaluate_expr(increment.clone())?;
+            }
         }
     }
 
-    fn visit_fun_stmt(&mut self, stmt: crate::stmt::FunStmt) -> Result<LoxObject, Return> {
+    fn visit_fun_stmt(&mut self, stmt: crate::stmt::FunStmt) -> Result<LoxObject, Unwind> {
         let fun_name = stmt.name.lexeme.clone();
-        let function = LoxFunction::new(stmt, self.environment.clone(), false);
+        // The function is stored in the scope it closes over, a reference cycle
+        // that keeps both alive until the interpreter goes away
+        let function = LoxFunction::new(stmt, Rc::clone(&self.environment), false);
         self.environment
+            .borrow_mut()
             .define(fun_name, LoxObject::FunCall(Box::new(function)));
         Ok(LoxObject::None)
     }
 
-    fn visit_return_stmt(&mut self, stmt: crate::stmt::ReturnStmt) -> Result<LoxObject, Return> {
+    fn visit_return_stmt(&mut self, stmt: crate::stmt::ReturnStmt) -> Result<LoxObject, Unwind> {
         let mut output = LoxObject::None;
         if let Some(value) = *stmt.value {
-            output = self.evaluate_expr(value)
+            output = self.evaluate_expr(value)?
         }
-        Err(Return { value: output })
+        Err(Unwind::Return(output))
     }
 
-    fn visit_class_stmt(&mut self, stmt: crate::stmt::ClassStmt) -> Result<LoxObject, Return> {
+    fn visit_class_stmt(&mut self, stmt: crate::stmt::ClassStmt) -> Result<LoxObject, Unwind> {
         let mut superclass = None;
         if let Some(superinit) = *stmt.superclass.clone() {
-            let super_exp = self.evaluate_expr(superinit);
+            let super_exp = self.evaluate_expr(superinit)?;
             match super_exp {
                 LoxObject::Class(x) => superclass = Some(x),
-                _ => panic!("Superclass must be a class."),
+                _ => {
+                    return Err(
+                        RuntimeError::raise(stmt.name, "Superclass must be a class.").into(),
+                    )
+                }
             }
         }
         self.environment
+            .borrow_mut()
             .define(stmt.name.lexeme.clone(), LoxObject::None);
+        let previous = Rc::clone(&self.environment);
         if let Some(superinit) = *stmt.superclass.clone() {
-            let super_exp = self.evaluate_expr(superinit);
-            self.environment = Environment::new_with_enclosing(self.environment.clone());
-            self.environment.define("super".to_string(), super_exp);
+            let super_exp = self.evaluate_expr(superinit)?;
+            self.environment = self.nested_scope();
+            self.environment
+                .borrow_mut()
+                .define("super".to_string(), super_exp);
         }
         let mut methods = HashMap::new();
         for method in stmt.methods {
             if let Stmt::Fun(stmt) = method {
                 let function = LoxFunction::new(
                     stmt.clone(),
-                    self.environment.clone(),
+                    Rc::clone(&self.environment),
                     stmt.name.lexeme.eq("init"),
                 );
                 methods.insert(stmt.name.lexeme, function);
@@ -398,15 +743,60 @@ impl StmtVisitor<LoxObject> for Interpreter {
         }
         let class = LoxClass::new(stmt.name.lexeme.clone(), superclass, methods);
 
-        if let Some(_superinit) = *stmt.superclass.clone() {
-            if let Some(enclosing) = self.environment.enclosing.clone() {
-                let env = enclosing.borrow();
-                self.environment = env.clone();
-            }
-        }
+        self.environment = previous;
 
         self.environment
-            .assign(stmt.name.lexeme, LoxObject::Class(class));
+            .borrow_mut()
+            .assign(&stmt.name, LoxObject::Class(class))?;
+        Ok(LoxObject::None)
+    }
+
+    fn visit_break_stmt(&mut self, _stmt: crate::stmt::BreakStmt) -> Result<LoxObject, Unwind> {
+        Err(Unwind::Break)
+    }
+
+    fn visit_continue_stmt(
+        &mut self,
+        _stmt: crate::stmt::ContinueStmt,
+    ) -> Result<LoxObject, Unwind> {
+        Err(Unwind::Continue)
+    }
+
+    fn visit_throw_stmt(&mut self, stmt: crate::stmt::ThrowStmt) -> Result<LoxObject, Unwind> {
+        let value = self.evaluate_expr(stmt.value)?;
+        // Rethrowing a caught error reports it with its original message
+        let msg = match &value {
+            LoxObject::Instance(instance) => instance.fields.borrow().get("message").cloned(),
+            _ => None,
+        };
+        let msg = match msg {
+            Some(msg) => msg.to_string(),
+            None => format!("Uncaught exception: {}", value),
+        };
+        let mut error = RuntimeError::raise(stmt.keyword, &msg);
+        error.thrown = Some(Box::new(value));
+        Err(Unwind::Throw(error))
+    }
+
+    fn visit_try_stmt(&mut self, stmt: crate::stmt::TryStmt) -> Result<LoxObject, Unwind> {
+        let mut result = self.execute_block(stmt.body);
+        if let Some(catch) = stmt.catch {
+            if let Err(Unwind::Throw(error) | Unwind::Error(error)) = result {
+                let exception = self.exception(error);
+                let environment = self.nested_scope();
+                environment
+                    .borrow_mut()
+                    .define(catch.name.lexeme, exception);
+                let previous = std::mem::replace(&mut self.environment, environment);
+                result = self.execute_block(catch.body);
+                self.environment = previous;
+            }
+        }
+        // A jump out of the finally block replaces whatever was unwinding
+        if let Some(finally) = stmt.finally {
+            self.execute_block(finally)?;
+        }
+        result?;
         Ok(LoxObject::None)
     }
 }
@@ -1,33 +1,68 @@
+pub mod bigint;
 pub mod callable;
 pub mod class;
 pub mod environment;
 pub mod exceptions;
 pub mod expr;
+pub mod inspect;
 pub mod instance;
 pub mod interpreter;
 pub mod logger;
+pub mod map;
+pub mod methods;
 pub mod parser;
+pub mod repl;
 pub mod resolver;
 pub mod scanner;
+pub mod session;
 pub mod stmt;
 pub mod testing_utils;
 pub mod tokens;
-pub mod utils;
 
-use crate::resolver::Resolver;
+use std::{cell::RefCell, rc::Rc};
+
+use crate::{
+    exceptions::LoxError, interpreter::Interpreter, resolver::Resolver, stmt::Stmt,
+    tokens::LoxObject,
+};
 
 pub fn run(source: &str) -> String {
+    match try_run(source) {
+        Ok(output) => output,
+        Err(error) => {
+            logger::lox_error(source, &error);
+            String::new()
+        }
+    }
+}
+
+pub fn try_run(source: &str) -> Result<String, LoxError> {
+    try_run_with_args(source, &[])
+}
+
+/// Like `try_run`, with `args` handed to the script as the global list `args`.
+pub fn try_run_with_args(source: &str, args: &[String]) -> Result<String, LoxError> {
+    let (statements, mut intr) = compile(source)?;
+    let args = args
+        .iter()
+        .map(|arg| LoxObject::String(arg.clone()))
+        .collect();
+    intr.define_global("args", LoxObject::List(Rc::new(RefCell::new(args))));
+    intr.interpret(statements)?;
+    Ok(intr.output)
+}
+
+/// Scans, parses and resolves `source`, returning its statements along with an
+/// interpreter that knows how to run them.
+pub fn compile(source: &str) -> Result<(Vec<Stmt>, Interpreter), LoxError> {
     let mut obj = scanner::Scanner::new(source);
-    let result = obj.scan_tokens();
-    // dbg!(&result);
+    let result = obj.scan_tokens().map_err(LoxError::Scan)?;
     let mut pars = parser::Parser::new(result);
-    let statements = pars.parse().unwrap();
-    // dbg!(&statements);
-    let mut intr = interpreter::Interpreter::new();
+    let statements = pars.parse().map_err(LoxError::Parse)?;
+    let mut intr = Interpreter::new();
     let mut resolver = Resolver::new(&mut intr);
-    resolver.begin_scope();
-    resolver.resolve_statements(statements.clone());
-    resolver.end_scope();
-    // dbg!(&intr.locals);
-    intr.interpret(statements)
+    resolver
+        .resolve(statements.clone())
+        .map_err(LoxError::Resolve)?;
+    Ok((statements, intr))
 }
@@ -1,17 +1,89 @@
-use crate::tokens::{Token, TokenType};
+use crate::{
+    exceptions::LoxError,
+    tokens::{Span, Token, TokenType},
+};
 
-pub fn error(line: usize, message: &str) {
-    report(line, "", message);
+pub fn format_error(line: usize, message: &str) -> String {
+    format_report(line, "", message)
 }
 
-pub fn error_token(token: Token, message: &str) {
+pub fn error_token(token: &Token, message: &str) {
+    eprintln!("{}", format_token_error(token, message));
+}
+
+pub fn format_token_error(token: &Token, message: &str) -> String {
     if token.token_type == TokenType::Eof {
-        report(token.line, "  at end", message);
+        format_report(token.line, " at end", message)
     } else {
-        report(token.line, &format!(" at '{}'", token.lexeme), message);
+        format_report(token.line, &format!(" at '{}'", token.lexeme), message)
     }
 }
 
-fn report(line: usize, location: &str, message: &str) {
-    eprintln!("[line {line}] Error{location}: {message}");
+fn format_report(line: usize, location: &str, message: &str) -> String {
+    format!("[line {line}] Error{location}: {message}")
+}
+
+pub fn lox_error(source: &str, error: &LoxError) {
+    eprintln!("{}", render_error(source, error));
+}
+
+/// Renders every diagnostic in `error` together with the source line it points at.
+pub fn render_error(source: &str, error: &LoxError) -> String {
+    match error {
+        LoxError::Scan(x) => x
+            .iter()
+            .map(|error| render(source, error.span, &error.to_string()))
+            .collect::<Vec<_>>()
+            .join("\n"),
+        LoxError::Parse(x) => x
+            .iter()
+            .map(|error| render(source, error.token.span, &error.to_string()))
+            .collect::<Vec<_>>()
+            .join("\n"),
+        LoxError::Resolve(x) => x
+            .iter()
+            .map(|error| render(source, error.token.span, &error.to_string()))
+            .collect::<Vec<_>>()
+            .join("\n"),
+        LoxError::Runtime(x) if x.trace.is_empty() => render(source, x.token.span, &x.to_string()),
+        LoxError::Runtime(x) => format!(
+            "{}\n{}",
+            render(source, x.token.span, &x.to_string()),
+            x.traceback()
+        ),
+    }
+}
+
+/// Formats `message` followed by the line of `source` containing `span`, with the
+/// spanned text underlined like so:
+///
+/// ```text
+/// [line 1] Error at '+': Operands must be numbers.
+/// 1 | print 1 + "a";
+///   |         ^
+/// ```
+pub fn render(source: &str, span: Span, message: &str) -> String {
+    let start = span.start.min(source.len());
+    let line_start = source[..start].rfind('\n').map_or(0, |i| i + 1);
+    let line_end = source[start..]
+        .find('\n')
+        .map_or(source.len(), |i| start + i);
+    let line_number = (source[..line_start].matches('\n').count() + 1).to_string();
+
+    // Keep tabs so the underline lines up with the source line above it
+    let indent: String = source[line_start..start]
+        .chars()
+        .map(|ch| if ch == '\t' { '\t' } else { ' ' })
+        .collect();
+    let width = source[start..span.end.clamp(start, line_end)]
+        .chars()
+        .count()
+        .max(1);
+
+    format!(
+        "{message}\n{line_number} | {}\n{} | {indent}^{}",
+        &source[line_start..line_end],
+        " ".repeat(line_number.len()),
+        "~".repeat(width - 1)
+    )
 }
@@ -1,45 +1,217 @@
-use std::io::Write;
-use std::io::{self};
-use std::{env, fs, process::exit};
+use std::io::{self, Read, Write};
+use std::{env, fs, panic, process::exit, thread};
 
-use lox::run;
+use lox::{
+    compile,
+    exceptions::LoxError,
+    inspect, logger,
+    repl::{is_complete, Command},
+    session::Session,
+    try_run_with_args,
+};
+
+/// Dumps one stage of the pipeline, see the `inspect` module.
+type Stage = fn(&str) -> Result<String, LoxError>;
+
+const USAGE: &str =
+    "Usage: lox [--tokens] [--ast] [--resolved] [--no-run] [script | -e code | -] [args...]";
+
+/// Where the program to run comes from.
+enum Script {
+    File(String),
+    /// Code given with `-e`.
+    Inline(String),
+    /// Read from stdin, picked with `-`.
+    Stdin,
+}
+
+/// What to do with a script, as picked on the command line.
+#[derive(Default)]
+struct Options {
+    tokens: bool,
+    ast: bool,
+    resolved: bool,
+    no_run: bool,
+    script: Option<Script>,
+    /// Arguments following the script, handed to it as `args`.
+    args: Vec<String>,
+}
+
+impl Options {
+    fn parse(args: Vec<String>) -> Result<Self, String> {
+        let mut options = Options::default();
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--tokens" => options.tokens = true,
+                "--ast" => options.ast = true,
+                "--resolved" => options.resolved = true,
+                "--no-run" => options.no_run = true,
+                "-e" => match args.next() {
+                    Some(code) => options.script = Some(Script::Inline(code)),
+                    None => return Err("Expected code after '-e'.".to_string()),
+                },
+                "-" => options.script = Some(Script::Stdin),
+                flag if flag.starts_with('-') => return Err(format!("Unknown option '{flag}'.")),
+                _ => options.script = Some(Script::File(arg)),
+            }
+            // Everything after the script belongs to it
+            if options.script.is_some() {
+                options.args = args.collect();
+                break;
+            }
+        }
+        Ok(options)
+    }
+
+    fn inspects(&self) -> bool {
+        self.tokens || self.ast || self.resolved || self.no_run
+    }
+}
+
+/// Stack of the thread that runs scripts, with room for `interpreter::MAX_FRAMES`
+/// nested Lox calls even in a debug build.
+const STACK_SIZE: usize = 256 * 1024 * 1024;
 
 fn main() {
+    let interpreter = thread::Builder::new()
+        .stack_size(STACK_SIZE)
+        .spawn(run)
+        .expect("failed to spawn the interpreter thread");
+    if let Err(payload) = interpreter.join() {
+        panic::resume_unwind(payload);
+    }
+}
+
+fn run() {
     let args: Vec<String> = env::args().skip(1).collect();
+    let options = match Options::parse(args) {
+        Ok(options) => options,
+        Err(msg) => {
+            eprintln!("{msg}");
+            println!("{USAGE}");
+            exit(64); // Command line usage error
+        }
+    };
 
-    if args.len() > 1 {
-        println!("Usage: lox [script]");
-        exit(64); // Command line usage error
-    }
-    if args.len() == 1 {
-        run_file(&args[0]);
-    } else {
-        run_prompt();
+    let source = match &options.script {
+        Some(Script::File(path)) => match fs::read_to_string(path) {
+            Ok(source) => source,
+            Err(_) => exit(65), // Data-format error
+        },
+        Some(Script::Inline(code)) => code.clone(),
+        Some(Script::Stdin) => {
+            let mut source = String::new();
+            if io::stdin().read_to_string(&mut source).is_err() {
+                exit(74); // Input/output error
+            }
+            source
+        }
+        None if options.inspects() => {
+            println!("{USAGE}");
+            exit(64); // Command line usage error
+        }
+        None => return run_prompt(),
+    };
+    run_source(&source, &options);
+}
+
+fn exit_code(error: &LoxError) -> i32 {
+    match error {
+        LoxError::Scan(_) | LoxError::Parse(_) | LoxError::Resolve(_) => 65, // Data-format error
+        LoxError::Runtime(_) => 70, // Internal software error
     }
 }
 
-fn run_file(file_path: &str) {
-    match fs::read_to_string(file_path) {
-        Ok(source) => {
-            run(&source);
+fn run_source(source: &str, options: &Options) {
+    let stages: [(bool, Stage); 3] = [
+        (options.tokens, inspect::tokens),
+        (options.ast, inspect::ast),
+        (options.resolved, inspect::resolved),
+    ];
+    for (_, stage) in stages.iter().filter(|(enabled, _)| *enabled) {
+        match stage(source) {
+            Ok(dump) => print!("{dump}"),
+            Err(error) => {
+                logger::lox_error(source, &error);
+                exit(exit_code(&error));
+            }
         }
-        Err(_) => exit(65), // Data-format error
+    }
+
+    let result = if options.no_run {
+        compile(source).map(|_| String::new())
+    } else {
+        try_run_with_args(source, &options.args)
+    };
+    if let Err(error) = result {
+        logger::lox_error(source, &error);
+        exit(exit_code(&error));
     }
 }
 
 fn run_prompt() {
+    let mut session = Session::new();
     let mut input = String::new();
+    let mut line = String::new();
 
     loop {
-        print!(">>> ");
+        print!("{}", if input.is_empty() { ">>> " } else { "... " });
         let _ = io::stdout().flush();
-        match io::stdin().read_line(&mut input) {
+        match io::stdin().read_line(&mut line) {
             Ok(0) => break,
             Ok(_) => {
-                print!("{}", run(&input));
+                if input.is_empty() && line.trim_start().starts_with(':') {
+                    match Command::parse(&line) {
+                        Ok(Command::Quit) => break,
+                        Ok(command) => run_command(&mut session, command),
+                        Err(msg) => eprintln!("{msg}"),
+                    }
+                    line.clear();
+                    continue;
+                }
+
+                // A blank continuation line submits the input as is
+                let blank = line.trim().is_empty();
+                input.push_str(&line);
+                line.clear();
+                if !blank && !is_complete(&input) {
+                    continue;
+                }
+                if let Err(error) = session.run(&input) {
+                    logger::lox_error(session.source(), &error);
+                }
                 input.clear();
             }
             Err(_) => exit(74), // Input/output error
         }
     }
 }
+
+fn run_command(session: &mut Session, command: Command) {
+    match command {
+        Command::Env => println!("{}", session.interpreter.environment.borrow()),
+        Command::Tokens(source) => match inspect::tokens(&source) {
+            Ok(tokens) => print!("{tokens}"),
+            Err(error) => logger::lox_error(&source, &error),
+        },
+        Command::Ast(source) => match inspect::ast(&source) {
+            Ok(ast) => print!("{ast}"),
+            Err(error) => logger::lox_error(&source, &error),
+        },
+        Command::Load(path) => match fs::read_to_string(&path) {
+            Ok(source) => {
+                if let Err(error) = session.run(&source) {
+                    logger::lox_error(session.source(), &error);
+                }
+            }
+            Err(error) => eprintln!("Could not read '{path}': {error}"),
+        },
+        Command::Help(name) => match session.doc(&name) {
+            Some(doc) => println!("{doc}"),
+            None => eprintln!("No documentation for '{name}'."),
+        },
+        Command::Reset => *session = Session::new(),
+        Command::Quit => (),
+    }
+}
@@ -0,0 +1,94 @@
+use std::{
+    collections::HashMap,
+    hash::{Hash, Hasher},
+};
+
+use crate::tokens::LoxObject;
+
+/// A map key. `LoxObject` equality is only reflexive once NaN is ruled out,
+/// which `LoxMap::insert` does.
+#[derive(Debug, Clone)]
+struct Key(LoxObject);
+
+impl PartialEq for Key {
+    fn eq(&self, other: &Self) -> bool {
+        self.0 == other.0
+    }
+}
+
+impl Eq for Key {}
+
+impl Hash for Key {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.0.hash(state)
+    }
+}
+
+/// Hash map from any Lox value to another, iterating in insertion order.
+#[derive(Debug, Clone, Default)]
+pub struct LoxMap {
+    entries: Vec<(LoxObject, LoxObject)>,
+    /// Position of each key in `entries`.
+    positions: HashMap<Key, usize>,
+}
+
+impl LoxMap {
+    pub fn new() -> Self {
+        LoxMap::default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn get(&self, key: &LoxObject) -> Option<&LoxObject> {
+        let position = self.positions.get(&Key(key.clone()))?;
+        Some(&self.entries[*position].1)
+    }
+
+    pub fn contains(&self, key: &LoxObject) -> bool {
+        self.positions.contains_key(&Key(key.clone()))
+    }
+
+    /// Sets the value of `key`, which keeps its place if it was already there.
+    pub fn insert(&mut self, key: LoxObject, value: LoxObject) -> Result<(), String> {
+        if matches!(key, LoxObject::Number(x) if x.is_nan()) {
+            return Err("Map keys can't be NaN.".to_string());
+        }
+        match self.positions.get(&Key(key.clone())) {
+            Some(position) => self.entries[*position].1 = value,
+            None => {
+                self.positions.insert(Key(key.clone()), self.entries.len());
+                self.entries.push((key, value));
+            }
+        }
+        Ok(())
+    }
+
+    pub fn remove(&mut self, key: &LoxObject) -> Option<LoxObject> {
+        let position = self.positions.remove(&Key(key.clone()))?;
+        let (_, value) = self.entries.remove(position);
+        for later in self.positions.values_mut() {
+            if *later > position {
+                *later -= 1;
+            }
+        }
+        Some(value)
+    }
+
+    pub fn keys(&self) -> impl Iterator<Item = &LoxObject> {
+        self.entries.iter().map(|(key, _)| key)
+    }
+
+    pub fn values(&self) -> impl Iterator<Item = &LoxObject> {
+        self.entries.iter().map(|(_, value)| value)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&LoxObject, &LoxObject)> {
+        self.entries.iter().map(|(key, value)| (key, value))
+    }
+}
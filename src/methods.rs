@@ -0,0 +1,172 @@
+use std::{cell::RefCell, rc::Rc};
+
+use crate::{
+    bigint::BigInt,
+    callable::{Arity, LoxCallable},
+    exceptions::RuntimeError,
+    interpreter::Interpreter,
+    map::LoxMap,
+    tokens::{LoxObject, Token},
+};
+
+/// A built-in method of a value that is not an instance, like `n.toBigInt` or
+/// `xs.push`, bound to that value.
+#[derive(Debug)]
+pub struct BoundMethod {
+    object: LoxObject,
+    /// Property name the method was looked up by, errors are reported here.
+    name: Token,
+    arity: Arity,
+}
+
+impl LoxCallable for BoundMethod {
+    fn call(
+        &self,
+        _interpreter: &mut Interpreter,
+        arguments: Vec<LoxObject>,
+    ) -> Result<LoxObject, RuntimeError> {
+        let method = self.name.lexeme.as_str();
+        match &self.object {
+            LoxObject::List(list) => list_method(list, method, arguments),
+            LoxObject::Map(map) => map_method(map, method, arguments),
+            number => number_method(number, method),
+        }
+        .map_err(|msg| RuntimeError::raise(self.name.clone(), &msg))
+    }
+
+    fn arity(&self) -> Arity {
+        self.arity
+    }
+
+    fn to_string(&self) -> String {
+        "<native fn>".to_string()
+    }
+
+    fn name(&self) -> String {
+        self.name.lexeme.clone()
+    }
+}
+
+/// Looks up the built-in method `name` of a value that is not an instance.
+pub fn get(object: LoxObject, name: Token) -> Result<LoxObject, RuntimeError> {
+    let arity = match &object {
+        LoxObject::Int(_) | LoxObject::BigInt(_) | LoxObject::Number(_) => {
+            number_arity(&name.lexeme)
+        }
+        LoxObject::List(_) => list_arity(&name.lexeme),
+        LoxObject::Map(_) => map_arity(&name.lexeme),
+        _ => return Err(RuntimeError::raise(name, "Only instances have properties.")),
+    };
+    let Some(arity) = arity else {
+        let msg = format!("Undefined property '{}'.", name.lexeme);
+        return Err(RuntimeError::raise(name, &msg));
+    };
+    Ok(LoxObject::Native(Rc::new(BoundMethod {
+        object,
+        name,
+        arity,
+    })))
+}
+
+/// Position in a list of length `len` that `index` refers to. `end` allows the
+/// position right after the last element.
+pub fn list_index(index: &LoxObject, len: usize, end: bool) -> Result<usize, String> {
+    let LoxObject::Int(index) = index else {
+        return Err("List index must be an integer.".to_string());
+    };
+    match usize::try_from(*index) {
+        Ok(index) if index < len || (end && index == len) => Ok(index),
+        _ => Err("List index out of range.".to_string()),
+    }
+}
+
+fn number_arity(method: &str) -> Option<Arity> {
+    match method {
+        "toBigInt" | "toInt" | "toFloat" => Some(Arity::Exact(0)),
+        _ => None,
+    }
+}
+
+/// Conversions between the kinds of number, floats are truncated towards zero.
+fn number_method(number: &LoxObject, method: &str) -> Result<LoxObject, String> {
+    let integer = || match number {
+        LoxObject::Number(x) => BigInt::from_f64(*x).ok_or("Number is not finite.".to_string()),
+        _ => Ok(number.as_bigint().unwrap_or_default()),
+    };
+    match method {
+        "toBigInt" => integer().map(LoxObject::BigInt),
+        "toInt" => integer().and_then(|x| {
+            x.to_i64()
+                .map(LoxObject::Int)
+                .ok_or("Number is too large for an integer.".to_string())
+        }),
+        "toFloat" => Ok(LoxObject::Number(number.as_float().unwrap_or_default())),
+        _ => unreachable!("checked by number_arity"),
+    }
+}
+
+fn list_arity(method: &str) -> Option<Arity> {
+    match method {
+        "len" | "pop" => Some(Arity::Exact(0)),
+        "push" | "remove" => Some(Arity::Exact(1)),
+        "insert" => Some(Arity::Exact(2)),
+        _ => None,
+    }
+}
+
+fn list_method(
+    list: &RefCell<Vec<LoxObject>>,
+    method: &str,
+    arguments: Vec<LoxObject>,
+) -> Result<LoxObject, String> {
+    let mut list = list.borrow_mut();
+    let mut arguments = arguments.into_iter();
+    let mut argument = || arguments.next().unwrap_or(LoxObject::None);
+    match method {
+        "len" => Ok(LoxObject::Int(list.len() as i64)),
+        "push" => {
+            list.push(argument());
+            Ok(LoxObject::None)
+        }
+        "pop" => list
+            .pop()
+            .ok_or("Can't pop from an empty list.".to_string()),
+        "insert" => {
+            let index = list_index(&argument(), list.len(), true)?;
+            list.insert(index, argument());
+            Ok(LoxObject::None)
+        }
+        "remove" => {
+            let index = list_index(&argument(), list.len(), false)?;
+            Ok(list.remove(index))
+        }
+        _ => unreachable!("checked by list_arity"),
+    }
+}
+
+fn map_arity(method: &str) -> Option<Arity> {
+    match method {
+        "keys" | "values" | "len" => Some(Arity::Exact(0)),
+        "has" | "remove" => Some(Arity::Exact(1)),
+        _ => None,
+    }
+}
+
+/// `remove` returns the value it took out, or `nil` if the key was missing.
+fn map_method(
+    map: &RefCell<LoxMap>,
+    method: &str,
+    arguments: Vec<LoxObject>,
+) -> Result<LoxObject, String> {
+    let mut map = map.borrow_mut();
+    let list = |items: Vec<LoxObject>| LoxObject::List(Rc::new(RefCell::new(items)));
+    let key = arguments.into_iter().next().unwrap_or(LoxObject::None);
+    match method {
+        "keys" => Ok(list(map.keys().cloned().collect())),
+        "values" => Ok(list(map.values().cloned().collect())),
+        "len" => Ok(LoxObject::Int(map.len() as i64)),
+        "has" => Ok(LoxObject::Bool(map.contains(&key))),
+        "remove" => Ok(map.remove(&key).unwrap_or(LoxObject::None)),
+        _ => unreachable!("checked by map_arity"),
+    }
+}
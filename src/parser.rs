@@ -1,47 +1,76 @@
+use std::collections::HashMap;
+
 use crate::{
     exceptions::ParserError,
     expr::{
-        BinaryExpr, CallExpr, Expr, GetExpr, GroupingExpr, LiteralExpr, LogicalExpr, SuperExpr,
-        ThisExpr, UnaryExpr, VariableExpr,
+        BinaryExpr, CallExpr, Expr, GetExpr, GroupingExpr, IndexExpr, IndexSetExpr,
+        InterpolationExpr, ListExpr, LiteralExpr, LogicalExpr, MapExpr, SuperExpr, ThisExpr,
+        UnaryExpr, VariableExpr,
     },
-    logger::error_token,
     stmt::{
-        BlockStmt, ClassStmt, ExpressionStmt, FunStmt, IfStmt, PrintStmt, ReturnStmt, Stmt,
-        VarStmt, WhileStmt,
+        BlockStmt, BreakStmt, CatchClause, ClassStmt, ContinueStmt, ExpressionStmt, FunStmt,
+        IfStmt, PrintStmt, ReturnStmt, Stmt, ThrowStmt, TryStmt, VarStmt, WhileStmt,
     },
-    tokens::{LoxObject, Token, TokenType},
+    tokens::{LoxObject, Span, Token, TokenType},
 };
 
 pub struct Parser {
     tokens: Vec<Token>,
     current: usize,
+    errors: Vec<ParserError>,
+    /// Doc comments keyed by the index of the token following them.
+    docs: HashMap<usize, String>,
 }
 
 impl Parser {
     pub fn new(tokens: Vec<Token>) -> Self {
-        Parser { tokens, current: 0 }
+        // Doc comments may appear anywhere, so they are set aside here and only
+        // picked up again by the declarations they precede
+        let mut docs: HashMap<usize, String> = HashMap::new();
+        let mut code = Vec::with_capacity(tokens.len());
+        for token in tokens {
+            if token.token_type == TokenType::DocComment {
+                let doc = docs.entry(code.len()).or_default();
+                if !doc.is_empty() {
+                    doc.push('\n');
+                }
+                doc.push_str(&token.literal.to_string());
+            } else {
+                code.push(token);
+            }
+        }
+        Parser {
+            tokens: code,
+            current: 0,
+            errors: Vec::new(),
+            docs,
+        }
     }
 
-    pub fn parse(&mut self) -> Result<Vec<Stmt>, ParserError> {
+    pub fn parse(&mut self) -> Result<Vec<Stmt>, Vec<ParserError>> {
         let mut result = Vec::new();
         while !self.is_at_end() {
             match self.declaration() {
                 Ok(stmt) => result.push(stmt),
-                Err(_) => {
-                    if let Some(_token) = self.tokens.get(self.current) {
-                        self.synchronize();
-                    }
+                Err(error) => {
+                    self.errors.push(error);
+                    self.synchronize();
                 }
             }
         }
-        Ok(result)
+        if self.errors.is_empty() {
+            Ok(result)
+        } else {
+            Err(std::mem::take(&mut self.errors))
+        }
     }
 
     fn declaration(&mut self) -> Result<Stmt, ParserError> {
+        let doc = self.docs.remove(&self.current);
         if self.match_token(vec![TokenType::Class]) {
-            self.class_declaration()
+            self.class_declaration(doc)
         } else if self.match_token(vec![TokenType::Fun]) {
-            self.function("function")
+            self.function("function", doc)
         } else if self.match_token(vec![TokenType::Var]) {
             self.variable_declaration()
         } else {
@@ -49,7 +78,8 @@ impl Parser {
         }
     }
 
-    fn class_declaration(&mut self) -> Result<Stmt, ParserError> {
+    fn class_declaration(&mut self, doc: Option<String>) -> Result<Stmt, ParserError> {
+        let start = self.previous().span;
         let name = self.consume(TokenType::Identifier, "Expect class name.")?;
         let mut superclass = None;
         if self.match_token(vec![TokenType::Less]) {
@@ -60,21 +90,39 @@ impl Parser {
 
         let mut methods = Vec::new();
         while !self.check(TokenType::RightBrace) && !self.is_at_end() {
-            methods.push(self.function("function")?)
+            let doc = self.docs.remove(&self.current);
+            methods.push(self.function("function", doc)?)
         }
         self.consume(TokenType::RightBrace, "Expect '}' after class body.")?;
-        Ok(Stmt::Class(ClassStmt::new(name, superclass, methods)))
+        Ok(Stmt::Class(ClassStmt::new(
+            name,
+            superclass,
+            methods,
+            doc,
+            self.span_from(start),
+        )))
     }
 
-    fn function(&mut self, kind: &str) -> Result<Stmt, ParserError> {
+    fn function(&mut self, kind: &str, doc: Option<String>) -> Result<Stmt, ParserError> {
         let name = self.consume(TokenType::Identifier, &format!("Expect {} name.", kind))?;
         self.consume(
             TokenType::LeftParen,
             &format!("Expect '(' after {} name.", kind),
         )?;
         let mut parameters = Vec::new();
+        let mut rest = None;
         if !self.check(TokenType::RightParen) {
             loop {
+                if self.match_token(vec![TokenType::Ellipsis]) {
+                    rest = Some(self.consume(TokenType::Identifier, "Expect parameter name.")?);
+                    if self.check(TokenType::Comma) {
+                        return Err(ParserError::raise(
+                            self.peek(),
+                            "Rest parameter must be last.",
+                        ));
+                    }
+                    break;
+                }
                 parameters.push(self.consume(TokenType::Identifier, "Expect parameter name.")?);
                 if !self.match_token(vec![TokenType::Comma]) {
                     break;
@@ -84,10 +132,29 @@ impl Parser {
         self.consume(TokenType::RightParen, "Expect ')' after parameters.")?;
         self.consume(TokenType::LeftBrace, "Expect '{' before body")?;
         let body = self.block()?;
-        Ok(Stmt::Fun(FunStmt::new(name, parameters, body)))
+        Ok(Stmt::Fun(FunStmt::new(
+            name.clone(),
+            parameters,
+            rest,
+            body,
+            doc,
+            self.span_from(name.span),
+        )))
     }
 
     fn statement(&mut self) -> Result<Stmt, ParserError> {
+        if self.match_token(vec![TokenType::Break]) {
+            let keyword = self.previous();
+            self.consume(TokenType::Semicolon, "Expect ';' after 'break'.")?;
+            let span = self.span_from(keyword.span);
+            return Ok(Stmt::Break(BreakStmt::new(keyword, span)));
+        }
+        if self.match_token(vec![TokenType::Continue]) {
+            let keyword = self.previous();
+            self.consume(TokenType::Semicolon, "Expect ';' after 'continue'.")?;
+            let span = self.span_from(keyword.span);
+            return Ok(Stmt::Continue(ContinueStmt::new(keyword, span)));
+        }
         if self.match_token(vec![TokenType::For]) {
             return self.for_statement();
         }
@@ -100,11 +167,22 @@ impl Parser {
         if self.match_token(vec![TokenType::Return]) {
             return self.return_statement();
         }
+        if self.match_token(vec![TokenType::Throw]) {
+            return self.throw_statement();
+        }
+        if self.match_token(vec![TokenType::Try]) {
+            return self.try_statement();
+        }
         if self.match_token(vec![TokenType::While]) {
             return self.while_statement();
         }
         if self.match_token(vec![TokenType::LeftBrace]) {
-            return Ok(Stmt::Block(BlockStmt::new(self.block()?)));
+            let start = self.previous().span;
+            let statements = self.block()?;
+            return Ok(Stmt::Block(BlockStmt::new(
+                statements,
+                self.span_from(start),
+            )));
         }
         self.expression_statement()
     }
@@ -116,10 +194,55 @@ impl Parser {
             value = Some(self.expression()?);
         }
         self.consume(TokenType::Semicolon, "Expect ';' after return.")?;
-        Ok(Stmt::Return(ReturnStmt::new(keyword, value)))
+        let span = self.span_from(keyword.span);
+        Ok(Stmt::Return(ReturnStmt::new(keyword, value, span)))
+    }
+
+    fn throw_statement(&mut self) -> Result<Stmt, ParserError> {
+        let keyword = self.previous();
+        let value = self.expression()?;
+        self.consume(TokenType::Semicolon, "Expect ';' after thrown value.")?;
+        let span = self.span_from(keyword.span);
+        Ok(Stmt::Throw(ThrowStmt::new(keyword, value, span)))
+    }
+
+    fn try_statement(&mut self) -> Result<Stmt, ParserError> {
+        let start = self.previous().span;
+        self.consume(TokenType::LeftBrace, "Expect '{' after 'try'.")?;
+        let body = self.block()?;
+
+        let mut catch = None;
+        if self.match_token(vec![TokenType::Catch]) {
+            self.consume(TokenType::LeftParen, "Expect '(' after 'catch'.")?;
+            let name = self.consume(TokenType::Identifier, "Expect exception name.")?;
+            self.consume(TokenType::RightParen, "Expect ')' after exception name.")?;
+            self.consume(TokenType::LeftBrace, "Expect '{' after catch clause.")?;
+            let body = self.block()?;
+            catch = Some(CatchClause { name, body });
+        }
+
+        let mut finally = None;
+        if self.match_token(vec![TokenType::Finally]) {
+            self.consume(TokenType::LeftBrace, "Expect '{' after 'finally'.")?;
+            finally = Some(self.block()?);
+        }
+
+        if catch.is_none() && finally.is_none() {
+            return Err(ParserError::raise(
+                self.peek(),
+                "Expect 'catch' or 'finally' after try block.",
+            ));
+        }
+        Ok(Stmt::Try(TryStmt::new(
+            body,
+            catch,
+            finally,
+            self.span_from(start),
+        )))
     }
 
     fn for_statement(&mut self) -> Result<Stmt, ParserError> {
+        let start = self.previous().span;
         self.consume(TokenType::LeftParen, "Expect '(' after 'for'")?;
 
         // Initializer
@@ -145,37 +268,39 @@ impl Parser {
             increment = Some(self.expression()?);
         }
         self.consume(TokenType::RightParen, "Expect ')' after for clauses")?;
-        let mut body = self.statement()?;
-
-        if let Some(expression) = increment {
-            body = Stmt::Block(BlockStmt::new(vec![
-                body,
-                Stmt::Expression(ExpressionStmt::new(expression)),
-            ]))
-        }
+        let body = self.statement()?;
+        let span = self.span_from(start);
 
+        // The increment stays apart from the body so that `continue` still runs it
         let condition = match condition {
             Some(x) => x,
-            None => Expr::Literal(LiteralExpr::new(LoxObject::Bool(true))),
+            None => Expr::Literal(LiteralExpr::new(LoxObject::Bool(true), start)),
         };
-        body = Stmt::While(WhileStmt::new(condition, body));
+        let mut body = Stmt::While(WhileStmt::new(condition, body, increment, span));
 
         if let Some(statement) = initializer {
-            body = Stmt::Block(BlockStmt::new(vec![statement, body]))
+            body = Stmt::Block(BlockStmt::new(vec![statement, body], span))
         }
 
         Ok(body)
     }
 
     fn while_statement(&mut self) -> Result<Stmt, ParserError> {
+        let start = self.previous().span;
         self.consume(TokenType::LeftParen, "Expect '(' after 'while'")?;
         let condition = self.expression()?;
         self.consume(TokenType::RightParen, "Expect ')' after condition")?;
         let body = self.statement()?;
-        Ok(Stmt::While(WhileStmt::new(condition, body)))
+        Ok(Stmt::While(WhileStmt::new(
+            condition,
+            body,
+            None,
+            self.span_from(start),
+        )))
     }
 
     fn if_statement(&mut self) -> Result<Stmt, ParserError> {
+        let start = self.previous().span;
         self.consume(TokenType::LeftParen, "Expect '(' after if")?;
         let condition = self.expression()?;
         self.consume(TokenType::RightParen, "Expect ')' after if")?;
@@ -186,7 +311,12 @@ impl Parser {
         } else {
             None
         };
-        Ok(Stmt::If(IfStmt::new(condition, then_branch, else_branch)))
+        Ok(Stmt::If(IfStmt::new(
+            condition,
+            then_branch,
+            else_branch,
+            self.span_from(start),
+        )))
     }
 
     fn block(&mut self) -> Result<Vec<Stmt>, ParserError> {
@@ -199,12 +329,14 @@ impl Parser {
     }
 
     fn print_statement(&mut self) -> Result<Stmt, ParserError> {
+        let start = self.previous().span;
         let value = self.expression()?;
         self.consume(TokenType::Semicolon, "Expect ';' after value.")?;
-        Ok(Stmt::Print(PrintStmt::new(value)))
+        Ok(Stmt::Print(PrintStmt::new(value, self.span_from(start))))
     }
 
     fn variable_declaration(&mut self) -> Result<Stmt, ParserError> {
+        let start = self.previous().span;
         let name = self.consume(TokenType::Identifier, "Expect variable name.")?;
         let mut initializer = None;
         if self.match_token(vec![TokenType::Equal]) {
@@ -215,13 +347,21 @@ impl Parser {
             TokenType::Semicolon,
             "Expect ';' after variable declaration.",
         )?;
-        Ok(Stmt::Var(VarStmt::new(name, initializer)))
+        Ok(Stmt::Var(VarStmt::new(
+            name,
+            initializer,
+            self.span_from(start),
+        )))
     }
 
     fn expression_statement(&mut self) -> Result<Stmt, ParserError> {
+        let start = self.peek().span;
         let value = self.expression()?;
         self.consume(TokenType::Semicolon, "Expect ';' after expression.")?;
-        Ok(Stmt::Expression(ExpressionStmt::new(value)))
+        Ok(Stmt::Expression(ExpressionStmt::new(
+            value,
+            self.span_from(start),
+        )))
     }
 
     fn expression(&mut self) -> Result<Expr, ParserError> {
@@ -229,28 +369,29 @@ impl Parser {
     }
 
     fn assignment(&mut self) -> Result<Expr, ParserError> {
-        let expr = self.or();
+        let expr = self.or()?;
         if self.match_token(vec![TokenType::Equal]) {
-            let _equals = self.previous();
+            let equals = self.previous();
             let value = self.assignment()?;
 
-            if let Ok(y) = expr {
-                return match y {
-                    Expr::Variable(x) => Ok(Expr::Assign(crate::expr::AssignExpr {
-                        name: x.name,
-                        value: Box::new(value),
-                    })),
-                    Expr::Get(x) => Ok(Expr::Set(crate::expr::SetExpr::new(
-                        *x.object, x.name, value,
-                    ))),
-                    _ => Err(ParserError::raise(String::from(
-                        "Don't know what I'm doing here",
-                    ))),
-                };
+            return match expr {
+                Expr::Variable(x) => Ok(Expr::Assign(crate::expr::AssignExpr::new(x.name, value))),
+                Expr::Get(x) => Ok(Expr::Set(crate::expr::SetExpr::new(
+                    *x.object, x.name, value,
+                ))),
+                Expr::Index(x) => Ok(Expr::IndexSet(IndexSetExpr::new(
+                    *x.object, x.bracket, *x.index, value,
+                ))),
+                _ => {
+                    // Report without unwinding, the parser is not confused here
+                    self.errors
+                        .push(ParserError::raise(equals, "Invalid assignment target."));
+                    Ok(expr)
+                }
             };
         }
 
-        expr
+        Ok(expr)
     }
 
     fn or(&mut self) -> Result<Expr, ParserError> {
@@ -351,7 +492,7 @@ impl Parser {
     fn factor(&mut self) -> Result<Expr, ParserError> {
         let mut expr = self.unary()?;
 
-        while self.match_token(vec![TokenType::Slash, TokenType::Star]) {
+        while self.match_token(vec![TokenType::Slash, TokenType::Star, TokenType::Percent]) {
             let operator = self.previous();
             let right = self.unary()?;
             expr = Expr::Binary(BinaryExpr::new(expr, operator, right));
@@ -377,6 +518,10 @@ impl Parser {
             } else if self.match_token(vec![TokenType::Dot]) {
                 let name = self.consume(TokenType::Identifier, "Expect property name after '.'")?;
                 expr = Expr::Get(GetExpr::new(expr, name))
+            } else if self.match_token(vec![TokenType::LeftBracket]) {
+                let index = self.expression()?;
+                let bracket = self.consume(TokenType::RightBracket, "Expect ']' after index.")?;
+                expr = Expr::Index(IndexExpr::new(expr, bracket, index))
             } else {
                 break;
             }
@@ -401,18 +546,59 @@ impl Parser {
         Ok(Expr::Call(CallExpr::new(callee, paren, arguments)))
     }
 
+    /// Parses the rest of a string literal whose first segment, ending in `${`, was
+    /// just matched. The scanner emits one `Interpolation` token per `${` and a
+    /// `String` token for the text after the last `}`.
+    fn interpolation(&mut self) -> Result<Expr, ParserError> {
+        let start = self.previous().span;
+        let mut parts = Vec::new();
+        loop {
+            let segment = self.previous();
+            parts.push(Expr::Literal(LiteralExpr::new(
+                segment.literal,
+                segment.span,
+            )));
+            parts.push(self.expression()?);
+            if self.match_token(vec![TokenType::Interpolation]) {
+                continue;
+            }
+            let end = self.consume(
+                TokenType::String,
+                "Expect '}' after interpolated expression.",
+            )?;
+            parts.push(Expr::Literal(LiteralExpr::new(end.literal, end.span)));
+            return Ok(Expr::Interpolation(InterpolationExpr::new(
+                parts,
+                start.to(end.span),
+            )));
+        }
+    }
+
     fn primary(&mut self) -> Result<Expr, ParserError> {
         if self.match_token(vec![TokenType::False]) {
-            return Ok(Expr::Literal(LiteralExpr::new(LoxObject::Bool(false))));
+            return Ok(Expr::Literal(LiteralExpr::new(
+                LoxObject::Bool(false),
+                self.previous().span,
+            )));
         }
         if self.match_token(vec![TokenType::True]) {
-            return Ok(Expr::Literal(LiteralExpr::new(LoxObject::Bool(true))));
+            return Ok(Expr::Literal(LiteralExpr::new(
+                LoxObject::Bool(true),
+                self.previous().span,
+            )));
         }
         if self.match_token(vec![TokenType::Nil]) {
-            return Ok(Expr::Literal(LiteralExpr::new(LoxObject::None)));
+            return Ok(Expr::Literal(LiteralExpr::new(
+                LoxObject::None,
+                self.previous().span,
+            )));
         }
         if self.match_token(vec![TokenType::Number, TokenType::String]) {
-            return Ok(Expr::Literal(LiteralExpr::new(self.previous().literal)));
+            let token = self.previous();
+            return Ok(Expr::Literal(LiteralExpr::new(token.literal, token.span)));
+        }
+        if self.match_token(vec![TokenType::Interpolation]) {
+            return self.interpolation();
         }
         if self.match_token(vec![TokenType::Super]) {
             let keyword = self.previous();
@@ -426,26 +612,67 @@ impl Parser {
         if self.match_token(vec![TokenType::Identifier]) {
             return Ok(Expr::Variable(VariableExpr::new(self.previous())));
         }
+        if self.match_token(vec![TokenType::LeftBracket]) {
+            let start = self.previous().span;
+            let mut elements = Vec::new();
+            if !self.check(TokenType::RightBracket) {
+                loop {
+                    elements.push(self.expression()?);
+                    if !self.match_token(vec![TokenType::Comma]) {
+                        break;
+                    }
+                }
+            }
+            self.consume(TokenType::RightBracket, "Expect ']' after list elements.")?;
+            return Ok(Expr::List(ListExpr::new(elements, self.span_from(start))));
+        }
+        // Statements starting with a `{` are blocks, so this is always a map
+        if self.match_token(vec![TokenType::LeftBrace]) {
+            let start = self.previous().span;
+            let mut entries = Vec::new();
+            if !self.check(TokenType::RightBrace) {
+                loop {
+                    let key = self.expression()?;
+                    self.consume(TokenType::Colon, "Expect ':' after map key.")?;
+                    entries.push((key, self.expression()?));
+                    if !self.match_token(vec![TokenType::Comma]) {
+                        break;
+                    }
+                }
+            }
+            let brace = self.consume(TokenType::RightBrace, "Expect '}' after map entries.")?;
+            return Ok(Expr::Map(MapExpr::new(
+                entries,
+                brace,
+                self.span_from(start),
+            )));
+        }
         if self.match_token(vec![TokenType::LeftParen]) {
+            let start = self.previous().span;
             let expr = self.expression()?;
             match self.consume(TokenType::RightParen, "Expect ')' after expression.") {
-                Ok(_token) => return Ok(Expr::Grouping(GroupingExpr::new(expr))),
+                Ok(token) => {
+                    return Ok(Expr::Grouping(GroupingExpr::new(
+                        expr,
+                        start.to(token.span),
+                    )))
+                }
                 Err(err) => return Err(err),
             };
         };
-        Err(ParserError::raise("Invalid primary expression".to_string()))
+        Err(ParserError::raise(self.peek(), "Expect expression."))
     }
 
-    fn consume(&mut self, token_type: TokenType, _message: &str) -> Result<Token, ParserError> {
+    fn consume(&mut self, token_type: TokenType, message: &str) -> Result<Token, ParserError> {
         if self.check(token_type) {
             Ok(self.advance())
         } else {
-            Err(ParserError::raise(_message.to_string()))
+            Err(ParserError::raise(self.peek(), message))
         }
     }
 
-    fn _throw_error(&self, token: Token, message: &str) {
-        error_token(token, message);
+    fn span_from(&self, start: Span) -> Span {
+        start.to(self.previous().span)
     }
 
     fn synchronize(&mut self) {
@@ -456,12 +683,16 @@ impl Parser {
             }
 
             match self.peek().token_type {
-                TokenType::Class
+                TokenType::Break
+                | TokenType::Class
+                | TokenType::Continue
                 | TokenType::For
                 | TokenType::Fun
                 | TokenType::If
                 | TokenType::Print
                 | TokenType::Return
+                | TokenType::Throw
+                | TokenType::Try
                 | TokenType::Var
                 | TokenType::While => return,
                 _ => (),
@@ -0,0 +1,73 @@
+use crate::{parser::Parser, scanner::Scanner, tokens::TokenType};
+
+/// A `:command` typed at the REPL instead of Lox code.
+#[derive(Debug, PartialEq)]
+pub enum Command {
+    Env,
+    Tokens(String),
+    Ast(String),
+    Load(String),
+    /// Show the doc comment of a function, class or `Class.method`.
+    Help(String),
+    Reset,
+    Quit,
+}
+
+impl Command {
+    pub fn parse(line: &str) -> Result<Self, String> {
+        let line = line.trim();
+        let (name, argument) = match line.split_once(char::is_whitespace) {
+            Some((name, argument)) => (name, argument.trim()),
+            None => (line, ""),
+        };
+        match name {
+            ":env" => Ok(Command::Env),
+            ":tokens" => Ok(Command::Tokens(argument.to_string())),
+            ":ast" => Ok(Command::Ast(argument.to_string())),
+            ":load" if argument.is_empty() => Err("Usage: :load <file>".to_string()),
+            ":load" => Ok(Command::Load(argument.to_string())),
+            ":help" if argument.is_empty() => Err("Usage: :help <name>".to_string()),
+            ":help" => Ok(Command::Help(argument.to_string())),
+            ":reset" => Ok(Command::Reset),
+            ":quit" => Ok(Command::Quit),
+            _ => Err(format!("Unknown command '{}'.", name)),
+        }
+    }
+}
+
+/// Whether `source` reads as finished input, or whether the REPL should keep
+/// reading lines because a string, bracket or statement is still open.
+pub fn is_complete(source: &str) -> bool {
+    let tokens = match Scanner::new(source).scan_tokens() {
+        Ok(tokens) => tokens,
+        Err(errors) => {
+            return !errors
+                .iter()
+                .any(|error| error.msg.starts_with("Unterminated"))
+        }
+    };
+
+    // A doc comment belongs to the declaration on the following lines
+    if tokens.len() > 1 && tokens[tokens.len() - 2].token_type == TokenType::DocComment {
+        return false;
+    }
+
+    let mut depth = 0;
+    for token in &tokens {
+        match token.token_type {
+            TokenType::LeftParen | TokenType::LeftBrace | TokenType::LeftBracket => depth += 1,
+            TokenType::RightParen | TokenType::RightBrace | TokenType::RightBracket => depth -= 1,
+            _ => (),
+        }
+    }
+    if depth > 0 {
+        return false;
+    }
+
+    match Parser::new(tokens).parse() {
+        Ok(_) => true,
+        Err(errors) => !errors
+            .iter()
+            .any(|error| error.token.token_type == TokenType::Eof),
+    }
+}
@@ -1,6 +1,7 @@
 use std::collections::HashMap;
 
 use crate::{
+    exceptions::ResolverError,
     expr::{Expr, ExprVisitor, ExprVisitorAcceptor},
     interpreter::Interpreter,
     stmt::{FunStmt, Stmt, StmtVisitor, StmtVisitorAcceptor},
@@ -27,18 +28,50 @@ pub struct Resolver<'a> {
     scopes: Vec<HashMap<String, bool>>,
     current_function: FunctionType,
     current_class: ClassType,
+    /// Number of loops around the statement being resolved, within its function.
+    loop_depth: usize,
+    errors: Vec<ResolverError>,
 }
 
 impl<'a> Resolver<'a> {
-    pub fn new(interpreter: &'a mut Interpreter) -> Resolver {
+    pub fn new(interpreter: &'a mut Interpreter) -> Resolver<'a> {
         Resolver {
             interpreter,
             scopes: Vec::new(),
             current_function: FunctionType::None,
             current_class: ClassType::None,
+            loop_depth: 0,
+            errors: Vec::new(),
         }
     }
 
+    /// Resolves a whole program, reporting every static error found in it.
+    pub fn resolve(&mut self, statements: Vec<Stmt>) -> Result<(), Vec<ResolverError>> {
+        self.resolve_in(&mut HashMap::new(), statements)
+    }
+
+    /// Resolves a program using `scope` as its top-level scope. The scope is only
+    /// updated with the program's declarations if it resolves without errors.
+    pub fn resolve_in(
+        &mut self,
+        scope: &mut HashMap<String, bool>,
+        statements: Vec<Stmt>,
+    ) -> Result<(), Vec<ResolverError>> {
+        self.scopes.push(scope.clone());
+        self.resolve_statements(statements);
+        let resolved = self.scopes.pop().unwrap_or_default();
+        if self.errors.is_empty() {
+            *scope = resolved;
+            Ok(())
+        } else {
+            Err(std::mem::take(&mut self.errors))
+        }
+    }
+
+    fn error(&mut self, token: Token, msg: &str) {
+        self.errors.push(ResolverError::raise(token, msg));
+    }
+
     pub fn begin_scope(&mut self) {
         self.scopes.push(HashMap::new());
     }
@@ -85,6 +118,11 @@ impl<'a> Resolver<'a> {
             Expr::Set(x) => self.resolve_expr(x),
             Expr::This(x) => self.resolve_expr(x),
             Expr::Super(x) => self.resolve_expr(x),
+            Expr::Interpolation(x) => self.resolve_expr(x),
+            Expr::List(x) => self.resolve_expr(x),
+            Expr::Map(x) => self.resolve_expr(x),
+            Expr::Index(x) => self.resolve_expr(x),
+            Expr::IndexSet(x) => self.resolve_expr(x),
         }
     }
 
@@ -99,21 +137,28 @@ impl<'a> Resolver<'a> {
             Stmt::Fun(x) => self.resolve_statement(x),
             Stmt::Return(x) => self.resolve_statement(x),
             Stmt::Class(x) => self.resolve_statement(x),
+            Stmt::Break(x) => self.resolve_statement(x),
+            Stmt::Continue(x) => self.resolve_statement(x),
+            Stmt::Throw(x) => self.resolve_statement(x),
+            Stmt::Try(x) => self.resolve_statement(x),
         }
     }
 
     fn resolve_function(&mut self, function: FunStmt, function_type: FunctionType) {
         let enclosing_function = self.current_function.clone();
         self.current_function = function_type;
+        // A loop around the declaration doesn't surround the body at run time
+        let enclosing_loop_depth = std::mem::take(&mut self.loop_depth);
         self.begin_scope();
-        for param in function.params {
-            self.declare(&param);
-            self.define(&param);
+        for param in function.params.iter().chain(&function.rest) {
+            self.declare(param);
+            self.define(param);
         }
         // Extra scope needed here as execute block opens up a new scope
         self.resolve_statements(function.body);
         self.end_scope();
         self.current_function = enclosing_function;
+        self.loop_depth = enclosing_loop_depth;
     }
 }
 
@@ -145,7 +190,7 @@ impl<'a> StmtVisitor<()> for Resolver<'a> {
     fn visit_block_stmt(
         &mut self,
         stmt: crate::stmt::BlockStmt,
-    ) -> Result<(), crate::exceptions::Return> {
+    ) -> Result<(), crate::exceptions::Unwind> {
         self.begin_scope();
         self.resolve_statements(stmt.statements);
         self.end_scope();
@@ -155,7 +200,7 @@ impl<'a> StmtVisitor<()> for Resolver<'a> {
     fn visit_var_stmt(
         &mut self,
         stmt: crate::stmt::VarStmt,
-    ) -> Result<(), crate::exceptions::Return> {
+    ) -> Result<(), crate::exceptions::Unwind> {
         self.declare(&stmt.name);
         if let Some(expr) = stmt.initializer {
             self.evaluate_expr(expr)
@@ -167,7 +212,7 @@ impl<'a> StmtVisitor<()> for Resolver<'a> {
     fn visit_fun_stmt(
         &mut self,
         stmt: crate::stmt::FunStmt,
-    ) -> Result<(), crate::exceptions::Return> {
+    ) -> Result<(), crate::exceptions::Unwind> {
         self.declare(&stmt.name);
         self.define(&stmt.name);
         self.resolve_function(stmt, FunctionType::Function);
@@ -177,7 +222,7 @@ impl<'a> StmtVisitor<()> for Resolver<'a> {
     fn visit_expression_stmt(
         &mut self,
         stmt: crate::stmt::ExpressionStmt,
-    ) -> Result<(), crate::exceptions::Return> {
+    ) -> Result<(), crate::exceptions::Unwind> {
         self.evaluate_expr(stmt.expression);
         Ok(())
     }
@@ -185,7 +230,7 @@ impl<'a> StmtVisitor<()> for Resolver<'a> {
     fn visit_if_stmt(
         &mut self,
         stmt: crate::stmt::IfStmt,
-    ) -> Result<(), crate::exceptions::Return> {
+    ) -> Result<(), crate::exceptions::Unwind> {
         self.evaluate_expr(stmt.condition);
         self.execute_stmt(*stmt.then_branch);
         if let Some(else_branch) = *stmt.else_branch {
@@ -197,7 +242,7 @@ impl<'a> StmtVisitor<()> for Resolver<'a> {
     fn visit_print_stmt(
         &mut self,
         stmt: crate::stmt::PrintStmt,
-    ) -> Result<(), crate::exceptions::Return> {
+    ) -> Result<(), crate::exceptions::Unwind> {
         self.evaluate_expr(stmt.expression);
         Ok(())
     }
@@ -205,12 +250,21 @@ impl<'a> StmtVisitor<()> for Resolver<'a> {
     fn visit_return_stmt(
         &mut self,
         stmt: crate::stmt::ReturnStmt,
-    ) -> Result<(), crate::exceptions::Return> {
+    ) -> Result<(), crate::exceptions::Unwind> {
         match self.current_function {
-            FunctionType::None => panic!("Can't return from top level"),
+            FunctionType::None => {
+                self.error(stmt.keyword.clone(), "Can't return from top-level code.")
+            }
             FunctionType::Function => (),
             FunctionType::Method => (),
-            FunctionType::Init => panic!("Can't return from init"),
+            FunctionType::Init => {
+                if stmt.value.is_some() {
+                    self.error(
+                        stmt.keyword.clone(),
+                        "Can't return a value from an initializer.",
+                    );
+                }
+            }
         }
 
         if let Some(value) = *stmt.value {
@@ -222,16 +276,21 @@ impl<'a> StmtVisitor<()> for Resolver<'a> {
     fn visit_while_stmt(
         &mut self,
         stmt: crate::stmt::WhileStmt,
-    ) -> Result<(), crate::exceptions::Return> {
+    ) -> Result<(), crate::exceptions::Unwind> {
         self.evaluate_expr(stmt.condition);
+        self.loop_depth += 1;
         self.execute_stmt(*stmt.body);
+        self.loop_depth -= 1;
+        if let Some(increment) = stmt.increment {
+            self.evaluate_expr(increment);
+        }
         Ok(())
     }
 
     fn visit_class_stmt(
         &mut self,
         stmt: crate::stmt::ClassStmt,
-    ) -> Result<(), crate::exceptions::Return> {
+    ) -> Result<(), crate::exceptions::Unwind> {
         let enclosing_class = self.current_class.clone();
         self.current_class = ClassType::Class;
 
@@ -240,7 +299,7 @@ impl<'a> StmtVisitor<()> for Resolver<'a> {
 
         if let Some(Expr::Variable(superclass)) = *stmt.superclass.clone() {
             if stmt.name.lexeme.eq(&superclass.name.lexeme) {
-                panic!("A class can't inherit from itself.");
+                self.error(superclass.name, "A class can't inherit from itself.");
             }
         }
         if let Some(superclass) = *stmt.superclass.clone() {
@@ -281,13 +340,69 @@ impl<'a> StmtVisitor<()> for Resolver<'a> {
 
         Ok(())
     }
+
+    fn visit_break_stmt(
+        &mut self,
+        stmt: crate::stmt::BreakStmt,
+    ) -> Result<(), crate::exceptions::Unwind> {
+        if self.loop_depth == 0 {
+            self.error(stmt.keyword, "Can't use 'break' outside of a loop.");
+        }
+        Ok(())
+    }
+
+    fn visit_continue_stmt(
+        &mut self,
+        stmt: crate::stmt::ContinueStmt,
+    ) -> Result<(), crate::exceptions::Unwind> {
+        if self.loop_depth == 0 {
+            self.error(stmt.keyword, "Can't use 'continue' outside of a loop.");
+        }
+        Ok(())
+    }
+
+    fn visit_throw_stmt(
+        &mut self,
+        stmt: crate::stmt::ThrowStmt,
+    ) -> Result<(), crate::exceptions::Unwind> {
+        self.evaluate_expr(stmt.value);
+        Ok(())
+    }
+
+    fn visit_try_stmt(
+        &mut self,
+        stmt: crate::stmt::TryStmt,
+    ) -> Result<(), crate::exceptions::Unwind> {
+        self.begin_scope();
+        self.resolve_statements(stmt.body);
+        self.end_scope();
+        if let Some(catch) = stmt.catch {
+            // The exception gets a scope of its own around the catch block
+            self.begin_scope();
+            self.declare(&catch.name);
+            self.define(&catch.name);
+            self.begin_scope();
+            self.resolve_statements(catch.body);
+            self.end_scope();
+            self.end_scope();
+        }
+        if let Some(finally) = stmt.finally {
+            self.begin_scope();
+            self.resolve_statements(finally);
+            self.end_scope();
+        }
+        Ok(())
+    }
 }
 
 impl<'a> ExprVisitor<()> for Resolver<'a> {
     fn visit_variable_expr(&mut self, expr: crate::expr::VariableExpr) {
         if let Some(innermost) = self.scopes.last() {
-            if let Some(false) = innermost.get(&expr.name.to_string()) {
-                panic!("Can't read local variable in it's own initializer");
+            if let Some(false) = innermost.get(&expr.name.lexeme) {
+                self.error(
+                    expr.name.clone(),
+                    "Can't read local variable in its own initializer.",
+                );
             }
         }
         self.resolve_local(expr.name);
@@ -337,17 +452,57 @@ impl<'a> ExprVisitor<()> for Resolver<'a> {
 
     fn visit_this_expr(&mut self, expr: crate::expr::ThisExpr) {
         if let ClassType::None = self.current_class {
-            panic!("this not allowed outside a class");
+            self.error(expr.name, "Can't use 'this' outside of a class.");
+            return;
         }
         self.resolve_local(expr.name);
     }
 
     fn visit_super_expr(&mut self, expr: crate::expr::SuperExpr) {
         match self.current_class {
-            ClassType::None => panic!("Can't use 'super' outside of a class."),
-            ClassType::Class => panic!("Can't use 'super' in a class with no subclass"),
+            ClassType::None => {
+                self.error(expr.keyword, "Can't use 'super' outside of a class.");
+                return;
+            }
+            ClassType::Class => {
+                self.error(
+                    expr.keyword,
+                    "Can't use 'super' in a class with no superclass.",
+                );
+                return;
+            }
             ClassType::Subclass => (),
         }
         self.resolve_local(expr.keyword);
     }
+
+    fn visit_interpolation_expr(&mut self, expr: crate::expr::InterpolationExpr) {
+        for part in expr.parts {
+            self.evaluate_expr(part);
+        }
+    }
+
+    fn visit_list_expr(&mut self, expr: crate::expr::ListExpr) {
+        for element in expr.elements {
+            self.evaluate_expr(element);
+        }
+    }
+
+    fn visit_map_expr(&mut self, expr: crate::expr::MapExpr) {
+        for (key, value) in expr.entries {
+            self.evaluate_expr(key);
+            self.evaluate_expr(value);
+        }
+    }
+
+    fn visit_index_expr(&mut self, expr: crate::expr::IndexExpr) {
+        self.evaluate_expr(*expr.object);
+        self.evaluate_expr(*expr.index);
+    }
+
+    fn visit_index_set_expr(&mut self, expr: crate::expr::IndexSetExpr) {
+        self.evaluate_expr(*expr.value);
+        self.evaluate_expr(*expr.object);
+        self.evaluate_expr(*expr.index);
+    }
 }
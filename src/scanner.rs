@@ -1,56 +1,120 @@
-use std::collections::HashMap;
+use std::num::IntErrorKind;
 
 use crate::{
-    logger,
-    tokens::{LoxObject, Token, TokenType},
+    bigint::BigInt,
+    exceptions::ScannerError,
+    tokens::{LoxObject, Span, Token, TokenType},
 };
 
 pub struct Scanner<'a> {
     source: &'a str,
     tokens: Vec<Token>,
+    errors: Vec<ScannerError>,
+    /// Byte index of the next character to scan.
     current: usize,
+    /// Byte index of the start of the current lexeme.
     start: usize,
     line: usize,
+    offset: usize,
+    /// Start and brace depth of each string whose `${` is still open.
+    interpolations: Vec<(usize, usize)>,
 }
 
 impl<'a> Scanner<'a> {
     pub fn new(source: &'a str) -> Self {
+        Scanner::new_with_offset(source, 0, 1)
+    }
+
+    /// Scans `source` as if it started at byte `offset` and line `line` of a larger
+    /// text, so that tokens from separate chunks never share a span.
+    pub fn new_with_offset(source: &'a str, offset: usize, line: usize) -> Self {
         Scanner {
             source,
             tokens: Vec::new(),
+            errors: Vec::new(),
             current: 0,
             start: 0,
-            line: 1,
+            line,
+            offset,
+            interpolations: Vec::new(),
         }
     }
 
-    pub fn scan_tokens(&mut self) -> Vec<Token> {
+    pub fn scan_tokens(&mut self) -> Result<Vec<Token>, Vec<ScannerError>> {
+        // Skip a `#!/usr/bin/env lox` line so scripts can be made executable. Any
+        // chunk may start with one, a script loaded into a REPL session included
+        if self.source.starts_with("#!") {
+            while self.peek() != '\n' && !self.is_at_end() {
+                self.advance();
+            }
+        }
         while !self.is_at_end() {
             self.start = self.current;
             self.scan_token();
         }
+        if let Some(&(start, _)) = self.interpolations.first() {
+            self.error_from(start, "Unterminated string.");
+        }
+        // Place the end right after the last token so errors "at end" point at
+        // the unfinished code rather than at trailing blank lines
+        let (line, span) = match self.tokens.last() {
+            Some(token) => (token.line, Span::new(token.span.end, token.span.end)),
+            None => (self.line, Span::new(self.offset, self.offset)),
+        };
         self.tokens.push(Token {
             token_type: TokenType::Eof,
             lexeme: "".to_string(),
             literal: LoxObject::None,
-            line: self.line,
-            current: self.current,
+            line,
+            span,
         });
-        self.tokens.clone()
+        if self.errors.is_empty() {
+            Ok(std::mem::take(&mut self.tokens))
+        } else {
+            Err(std::mem::take(&mut self.errors))
+        }
     }
 
     fn scan_token(&mut self) {
         match self.advance() {
             '(' => self.add_token(TokenType::LeftParen),
             ')' => self.add_token(TokenType::RightParen),
-            '{' => self.add_token(TokenType::LeftBrace),
-            '}' => self.add_token(TokenType::RightBrace),
+            '{' => {
+                if let Some((_, depth)) = self.interpolations.last_mut() {
+                    *depth += 1;
+                }
+                self.add_token(TokenType::LeftBrace)
+            }
+            '}' => match self.interpolations.last_mut() {
+                // Closes a `${`, the string carries on from here
+                Some((_, 0)) => {
+                    self.interpolations.pop();
+                    self.string();
+                }
+                Some((_, depth)) => {
+                    *depth -= 1;
+                    self.add_token(TokenType::RightBrace)
+                }
+                None => self.add_token(TokenType::RightBrace),
+            },
+            '[' => self.add_token(TokenType::LeftBracket),
+            ']' => self.add_token(TokenType::RightBracket),
+            ':' => self.add_token(TokenType::Colon),
             ',' => self.add_token(TokenType::Comma),
-            '.' => self.add_token(TokenType::Dot),
+            '.' => {
+                if self.peek() == '.' && self.peek_next() == '.' {
+                    self.advance();
+                    self.advance();
+                    self.add_token(TokenType::Ellipsis)
+                } else {
+                    self.add_token(TokenType::Dot)
+                }
+            }
             '-' => self.add_token(TokenType::Minus),
             '+' => self.add_token(TokenType::Plus),
             ';' => self.add_token(TokenType::Semicolon),
             '*' => self.add_token(TokenType::Star),
+            '%' => self.add_token(TokenType::Percent),
             '!' => {
                 if self.match_next('=') {
                     self.add_token(TokenType::BangEqual)
@@ -81,9 +145,18 @@ impl<'a> Scanner<'a> {
             }
             '/' => {
                 if self.match_next('/') {
+                    // `///` documents what follows, `////` is a plain comment again
+                    let doc = self.peek() == '/' && self.peek_next() != '/';
                     while self.peek() != '\n' && !self.is_at_end() {
                         self.advance();
                     }
+                    if doc {
+                        let text = self.lexeme()[3..].trim_end();
+                        let text = text.strip_prefix(' ').unwrap_or(text).to_string();
+                        self.add_token_with_literal(TokenType::DocComment, LoxObject::String(text));
+                    }
+                } else if self.match_next('*') {
+                    self.block_comment();
                 } else {
                     self.add_token(TokenType::Slash);
                 }
@@ -97,7 +170,7 @@ impl<'a> Scanner<'a> {
                 } else if self.is_alpha(c) {
                     self.identifier();
                 } else {
-                    logger::error(self.line, "Unexpected character.");
+                    self.error("Unexpected character.");
                 }
             }
         }
@@ -116,162 +189,312 @@ impl<'a> Scanner<'a> {
             self.advance();
         }
 
-        let substring: String = self
-            .source
-            .chars()
-            .skip(self.start)
-            .take(self.current - self.start)
-            .collect();
-
-        let keywords = self.get_keywords_table();
-
-        match keywords.get(&substring) {
-            Some(&keyword) => self.add_token(keyword),
+        match keyword(self.lexeme()) {
+            Some(keyword) => self.add_token(keyword),
             None => self.add_token(TokenType::Identifier),
         }
     }
 
-    fn get_keywords_table(&self) -> HashMap<String, TokenType> {
-        let mut keywords = HashMap::new();
-        keywords.insert("and".to_string(), TokenType::And);
-        keywords.insert("class".to_string(), TokenType::Class);
-        keywords.insert("else".to_string(), TokenType::Else);
-        keywords.insert("false".to_string(), TokenType::False);
-        keywords.insert("for".to_string(), TokenType::For);
-        keywords.insert("fun".to_string(), TokenType::Fun);
-        keywords.insert("if".to_string(), TokenType::If);
-        keywords.insert("nil".to_string(), TokenType::Nil);
-        keywords.insert("or".to_string(), TokenType::Or);
-        keywords.insert("print".to_string(), TokenType::Print);
-        keywords.insert("return".to_string(), TokenType::Return);
-        keywords.insert("super".to_string(), TokenType::Super);
-        keywords.insert("this".to_string(), TokenType::This);
-        keywords.insert("true".to_string(), TokenType::True);
-        keywords.insert("var".to_string(), TokenType::Var);
-        keywords.insert("while".to_string(), TokenType::While);
-        keywords
+    /// Skips a `/* ... */` comment, which may contain other block comments.
+    fn block_comment(&mut self) {
+        let mut depth = 1;
+        while depth > 0 && !self.is_at_end() {
+            match self.advance() {
+                '/' if self.match_next('*') => depth += 1,
+                '*' if self.match_next('/') => depth -= 1,
+                '\n' => self.line += 1,
+                _ => (),
+            }
+        }
+        if depth > 0 {
+            self.error("Unterminated block comment.");
+        }
     }
 
     fn number(&mut self) {
-        while self.peek().is_ascii_digit() {
-            self.advance();
+        if self.lexeme() == "0" {
+            let radix = match self.peek() {
+                'x' | 'X' => Some((16, "hex")),
+                'o' | 'O' => Some((8, "octal")),
+                'b' | 'B' => Some((2, "binary")),
+                _ => None,
+            };
+            if let Some((radix, name)) = radix {
+                self.advance();
+                return self.radix_number(radix, name);
+            }
         }
 
+        self.digits();
         if self.peek() == '.' && self.peek_next().is_ascii_digit() {
             self.advance();
-            while self.peek().is_ascii_digit() {
+            self.digits();
+        }
+        if matches!(self.peek(), 'e' | 'E') {
+            let sign = matches!(self.peek_next(), '+' | '-');
+            let rest = &self.source[self.current + 1..];
+            let digit = rest.chars().nth(usize::from(sign));
+            if digit.is_some_and(|ch| ch.is_ascii_digit()) {
+                self.advance();
+                if sign {
+                    self.advance();
+                }
+                self.digits();
+            }
+        }
+
+        if self.is_alphanumeric(self.peek()) || !separated(self.lexeme(), 10) {
+            while self.is_alphanumeric(self.peek()) {
                 self.advance();
             }
+            self.error("Malformed number.");
+            return;
+        }
+        let text = self.lexeme().replace('_', "");
+        // Without a fraction or exponent the literal is an integer
+        if text.bytes().all(|byte| byte.is_ascii_digit()) {
+            match text.parse::<i64>() {
+                Ok(value) => self.add_token_with_literal(TokenType::Number, LoxObject::Int(value)),
+                Err(_) => match BigInt::parse(&text) {
+                    Some(value) => {
+                        self.add_token_with_literal(TokenType::Number, LoxObject::BigInt(value))
+                    }
+                    None => self.error("Malformed number."),
+                },
+            }
+            return;
+        }
+        match text.parse::<f64>() {
+            Ok(value) => self.add_token_with_literal(TokenType::Number, LoxObject::Number(value)),
+            Err(_) => self.error("Malformed number."),
+        }
+    }
+
+    /// Consumes decimal digits and `_` separators.
+    fn digits(&mut self) {
+        while self.peek().is_ascii_digit() || self.peek() == '_' {
+            self.advance();
+        }
+    }
+
+    /// Scans the digits of a `0x`, `0o` or `0b` literal, the prefix is already
+    /// consumed.
+    fn radix_number(&mut self, radix: u32, name: &str) {
+        while self.is_alphanumeric(self.peek()) {
+            self.advance();
+        }
+        let digits = &self.lexeme()[2..];
+        if digits.is_empty() {
+            self.error(&format!("Expect digits after '{}'.", &self.lexeme()[..2]));
+            return;
+        }
+        if !separated(digits, radix) {
+            self.error(&format!("Malformed {name} number."));
+            return;
+        }
+        let digits = digits.replace('_', "");
+        let literal = match i64::from_str_radix(&digits, radix) {
+            Ok(value) => Some(LoxObject::Int(value)),
+            Err(error) if *error.kind() == IntErrorKind::PosOverflow => {
+                BigInt::parse_radix(&digits, radix).map(LoxObject::BigInt)
+            }
+            Err(_) => None,
+        };
+        match literal {
+            Some(literal) => self.add_token_with_literal(TokenType::Number, literal),
+            None => self.error(&format!("Invalid digit in {name} number.")),
         }
-        let substring: String = self
-            .source
-            .chars()
-            .skip(self.start)
-            .take(self.current - self.start)
-            .collect();
-        self.add_token_with_literal(
-            TokenType::Number,
-            LoxObject::Number(substring.parse::<f32>().unwrap()),
-        )
     }
 
     fn string(&mut self) {
+        let mut literal = String::new();
         while self.peek() != '"' && !self.is_at_end() {
-            if self.peek() == '\n' {
-                self.line += 1;
+            if self.peek() == '$' && self.peek_next() == '{' {
+                self.advance();
+                self.advance();
+                self.interpolations.push((self.start, 0));
+                let literal = LoxObject::String(literal);
+                self.add_token_with_literal(TokenType::Interpolation, literal);
+                return;
+            }
+            match self.advance() {
+                '\\' => {
+                    if let Some(ch) = self.escape() {
+                        literal.push(ch);
+                    }
+                }
+                ch => {
+                    if ch == '\n' {
+                        self.line += 1;
+                    }
+                    literal.push(ch);
+                }
             }
-            self.advance();
         }
 
         if self.is_at_end() {
-            logger::error(self.line, "Unterminated string.");
+            self.error("Unterminated string.");
             return;
         }
 
         self.advance();
 
-        let literal: String = self
-            .source
-            .chars()
-            .skip(self.start + 1)
-            .take(self.current - 1 - (self.start + 1))
-            .collect();
         self.add_token_with_literal(TokenType::String, LoxObject::String(literal));
     }
 
-    fn peek(&self) -> char {
+    /// Decodes the escape sequence following a backslash, reporting an error and
+    /// returning `None` if it is not a valid one.
+    fn escape(&mut self) -> Option<char> {
+        let start = self.current - 1;
         if self.is_at_end() {
-            '\0'
-        } else if let Some(ch) = self.source.chars().nth(self.current) {
-            ch
-        } else {
-            '\0'
+            // Reported as an unterminated string
+            return None;
+        }
+        match self.advance() {
+            'n' => Some('\n'),
+            't' => Some('\t'),
+            'r' => Some('\r'),
+            '0' => Some('\0'),
+            '"' => Some('"'),
+            '$' => Some('$'),
+            '\\' => Some('\\'),
+            'u' => {
+                if !self.match_next('{') {
+                    self.error_from(start, "Expect '{' after '\\u'.");
+                    return None;
+                }
+                let digits_start = self.current;
+                while self.peek().is_ascii_hexdigit() {
+                    self.advance();
+                }
+                let digits = &self.source[digits_start..self.current];
+                if !self.match_next('}') {
+                    self.error_from(start, "Expect '}' after unicode escape.");
+                    return None;
+                }
+                let ch = u32::from_str_radix(digits, 16)
+                    .ok()
+                    .filter(|_| digits.len() <= 6)
+                    .and_then(char::from_u32);
+                if ch.is_none() {
+                    self.error_from(start, "Invalid unicode escape.");
+                }
+                ch
+            }
+            ch => {
+                if ch == '\n' {
+                    self.line += 1;
+                }
+                self.error_from(start, &format!("Invalid escape sequence '\\{ch}'."));
+                None
+            }
         }
     }
 
+    fn peek(&self) -> char {
+        self.source[self.current..].chars().next().unwrap_or('\0')
+    }
+
     fn peek_next(&self) -> char {
-        if self.current + 1 >= self.source.chars().count() {
-            '\0'
-        } else if let Some(ch) = self.source.chars().nth(self.current + 1) {
-            ch
-        } else {
-            '\0'
-        }
+        self.source[self.current..].chars().nth(1).unwrap_or('\0')
     }
 
     fn match_next(&mut self, expected: char) -> bool {
-        if self.is_at_end() {
+        if self.is_at_end() || self.peek() != expected {
             return false;
         }
-        if let Some(ch) = self.source.chars().nth(self.current) {
-            if ch != expected {
-                return false;
-            }
-        }
 
-        self.current += 1;
+        self.current += expected.len_utf8();
         true
     }
 
     fn advance(&mut self) -> char {
-        let ch = self.source.chars().nth(self.current).unwrap();
-        self.current += 1;
+        let ch = self.peek();
+        self.current += ch.len_utf8();
         ch
     }
 
+    /// Text of the lexeme currently being scanned.
+    fn lexeme(&self) -> &'a str {
+        &self.source[self.start..self.current]
+    }
+
     fn add_token(&mut self, token: TokenType) {
-        let lexeme = self
-            .source
-            .chars()
-            .skip(self.start)
-            .take(self.current - self.start)
-            .collect();
         self.tokens.push(Token {
             token_type: token,
-            lexeme,
+            lexeme: self.lexeme().to_string(),
             literal: LoxObject::None,
             line: self.line,
-            current: self.current,
+            span: self.span(),
         })
     }
 
     fn add_token_with_literal(&mut self, token: TokenType, literal: LoxObject) {
-        let lexeme = self
-            .source
-            .chars()
-            .skip(self.start)
-            .take(self.current - self.start)
-            .collect();
         self.tokens.push(Token {
             token_type: token,
-            lexeme,
+            lexeme: self.lexeme().to_string(),
             literal,
             line: self.line,
-            current: self.current,
+            span: self.span(),
         })
     }
 
+    fn error(&mut self, msg: &str) {
+        self.error_from(self.start, msg);
+    }
+
+    /// Reports an error spanning from byte `start` of the source to the cursor.
+    fn error_from(&mut self, start: usize, msg: &str) {
+        let span = Span::new(self.offset + start, self.offset + self.current);
+        let error = ScannerError::raise(self.line, span, msg);
+        self.errors.push(error);
+    }
+
+    /// Byte span of the lexeme currently being scanned.
+    fn span(&self) -> Span {
+        Span::new(self.offset + self.start, self.offset + self.current)
+    }
+
     fn is_at_end(&self) -> bool {
         self.current >= self.source.len()
     }
 }
+
+fn keyword(text: &str) -> Option<TokenType> {
+    match text {
+        "and" => Some(TokenType::And),
+        "break" => Some(TokenType::Break),
+        "catch" => Some(TokenType::Catch),
+        "class" => Some(TokenType::Class),
+        "continue" => Some(TokenType::Continue),
+        "else" => Some(TokenType::Else),
+        "false" => Some(TokenType::False),
+        "finally" => Some(TokenType::Finally),
+        "for" => Some(TokenType::For),
+        "fun" => Some(TokenType::Fun),
+        "if" => Some(TokenType::If),
+        "nil" => Some(TokenType::Nil),
+        "or" => Some(TokenType::Or),
+        "print" => Some(TokenType::Print),
+        "return" => Some(TokenType::Return),
+        "super" => Some(TokenType::Super),
+        "this" => Some(TokenType::This),
+        "throw" => Some(TokenType::Throw),
+        "true" => Some(TokenType::True),
+        "try" => Some(TokenType::Try),
+        "var" => Some(TokenType::Var),
+        "while" => Some(TokenType::While),
+        _ => None,
+    }
+}
+
+/// Whether every `_` in `text` sits between two digits of the given radix.
+fn separated(text: &str, radix: u32) -> bool {
+    let chars: Vec<char> = text.chars().collect();
+    chars.iter().enumerate().all(|(i, &ch)| {
+        ch != '_'
+            || (i > 0
+                && chars[i - 1].is_digit(radix)
+                && chars.get(i + 1).is_some_and(|next| next.is_digit(radix)))
+    })
+}
@@ -0,0 +1,110 @@
+use std::collections::HashMap;
+
+use crate::{
+    exceptions::LoxError,
+    interpreter::Interpreter,
+    parser::Parser,
+    resolver::Resolver,
+    scanner::Scanner,
+    stmt::{FunStmt, Stmt},
+    tokens::LoxObject,
+};
+
+/// Long lived interpreter state for running a program one chunk at a time, as the
+/// REPL does. Variables, functions and classes declared by one call to `run` stay
+/// visible to the following ones.
+pub struct Session {
+    pub interpreter: Interpreter,
+    /// Top-level scope of the resolver, carried over between chunks.
+    scope: HashMap<String, bool>,
+    /// Every chunk run so far, diagnostics point into this.
+    source: String,
+    line: usize,
+    /// Doc comments of the functions, classes and methods declared so far, by
+    /// name. Methods are named `Class.method`.
+    docs: HashMap<String, String>,
+}
+
+impl Session {
+    pub fn new() -> Self {
+        Session {
+            interpreter: Interpreter::new(),
+            scope: HashMap::new(),
+            source: String::new(),
+            line: 1,
+            docs: HashMap::new(),
+        }
+    }
+
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+
+    /// The doc comment of the function, class or `Class.method` called `name`.
+    pub fn doc(&self, name: &str) -> Option<&str> {
+        self.docs.get(name).map(String::as_str)
+    }
+
+    fn collect_docs(&mut self, statements: &[Stmt]) {
+        for statement in statements {
+            match statement {
+                Stmt::Fun(fun) => {
+                    if let Some(doc) = &fun.doc {
+                        self.docs.insert(fun.name.lexeme.clone(), doc.clone());
+                    }
+                }
+                Stmt::Class(class) => {
+                    if let Some(doc) = &class.doc {
+                        self.docs.insert(class.name.lexeme.clone(), doc.clone());
+                    }
+                    for method in &class.methods {
+                        if let Stmt::Fun(FunStmt {
+                            name,
+                            doc: Some(doc),
+                            ..
+                        }) = method
+                        {
+                            let name = format!("{}.{}", class.name.lexeme, name.lexeme);
+                            self.docs.insert(name, doc.clone());
+                        }
+                    }
+                }
+                _ => (),
+            }
+        }
+    }
+
+    /// Runs `input` and returns everything it printed. The value of an expression
+    /// statement is echoed unless it is `nil`.
+    pub fn run(&mut self, input: &str) -> Result<String, LoxError> {
+        let mut scanner = Scanner::new_with_offset(input, self.source.len(), self.line);
+        self.source.push_str(input);
+        if !input.ends_with('\n') {
+            self.source.push('\n');
+        }
+        self.line = self.source.matches('\n').count() + 1;
+
+        let tokens = scanner.scan_tokens().map_err(LoxError::Scan)?;
+        let statements = Parser::new(tokens).parse().map_err(LoxError::Parse)?;
+        Resolver::new(&mut self.interpreter)
+            .resolve_in(&mut self.scope, statements.clone())
+            .map_err(LoxError::Resolve)?;
+        self.collect_docs(&statements);
+
+        self.interpreter.output.clear();
+        for statement in statements {
+            let echo = matches!(statement, Stmt::Expression(_));
+            let value = self.interpreter.execute_statement(statement)?;
+            if echo && value != LoxObject::None {
+                self.interpreter.print(&value);
+            }
+        }
+        Ok(std::mem::take(&mut self.interpreter.output))
+    }
+}
+
+impl Default for Session {
+    fn default() -> Self {
+        Self::new()
+    }
+}
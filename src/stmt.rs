@@ -1,25 +1,29 @@
 use std::fmt::Display;
 
 use crate::{
-    exceptions::Return,
-    expr::{Expr},
-    tokens::Token,
+    exceptions::Unwind,
+    expr::Expr,
+    tokens::{Span, Token},
 };
 
 pub trait StmtVisitor<T> {
-    fn visit_expression_stmt(&mut self, stmt: ExpressionStmt) -> Result<T, Return>;
-    fn visit_print_stmt(&mut self, stmt: PrintStmt) -> Result<T, Return>;
-    fn visit_var_stmt(&mut self, stmt: VarStmt) -> Result<T, Return>;
-    fn visit_block_stmt(&mut self, stmt: BlockStmt) -> Result<T, Return>;
-    fn visit_if_stmt(&mut self, stmt: IfStmt) -> Result<T, Return>;
-    fn visit_while_stmt(&mut self, stmt: WhileStmt) -> Result<T, Return>;
-    fn visit_fun_stmt(&mut self, stmt: FunStmt) -> Result<T, Return>;
-    fn visit_return_stmt(&mut self, stmt: ReturnStmt) -> Result<T, Return>;
-    fn visit_class_stmt(&mut self, stmt: ClassStmt) -> Result<T, Return>;
+    fn visit_expression_stmt(&mut self, stmt: ExpressionStmt) -> Result<T, Unwind>;
+    fn visit_print_stmt(&mut self, stmt: PrintStmt) -> Result<T, Unwind>;
+    fn visit_var_stmt(&mut self, stmt: VarStmt) -> Result<T, Unwind>;
+    fn visit_block_stmt(&mut self, stmt: BlockStmt) -> Result<T, Unwind>;
+    fn visit_if_stmt(&mut self, stmt: IfStmt) -> Result<T, Unwind>;
+    fn visit_while_stmt(&mut self, stmt: WhileStmt) -> Result<T, Unwind>;
+    fn visit_fun_stmt(&mut self, stmt: FunStmt) -> Result<T, Unwind>;
+    fn visit_return_stmt(&mut self, stmt: ReturnStmt) -> Result<T, Unwind>;
+    fn visit_class_stmt(&mut self, stmt: ClassStmt) -> Result<T, Unwind>;
+    fn visit_break_stmt(&mut self, stmt: BreakStmt) -> Result<T, Unwind>;
+    fn visit_continue_stmt(&mut self, stmt: ContinueStmt) -> Result<T, Unwind>;
+    fn visit_throw_stmt(&mut self, stmt: ThrowStmt) -> Result<T, Unwind>;
+    fn visit_try_stmt(&mut self, stmt: TryStmt) -> Result<T, Unwind>;
 }
 
 pub trait StmtVisitorAcceptor<T> {
-    fn accept(&self, visitor: &mut impl StmtVisitor<T>) -> Result<T, Return>;
+    fn accept(&self, visitor: &mut impl StmtVisitor<T>) -> Result<T, Unwind>;
 }
 
 #[derive(Clone, Debug)]
@@ -33,37 +37,90 @@ pub enum Stmt {
     Fun(FunStmt),
     Return(ReturnStmt),
     Class(ClassStmt),
+    Break(BreakStmt),
+    Continue(ContinueStmt),
+    Throw(ThrowStmt),
+    Try(TryStmt),
+}
+
+impl Stmt {
+    pub fn span(&self) -> Span {
+        match self {
+            Self::Expression(x) => x.span,
+            Self::Print(x) => x.span,
+            Self::Var(x) => x.span,
+            Self::Block(x) => x.span,
+            Self::If(x) => x.span,
+            Self::While(x) => x.span,
+            Self::Fun(x) => x.span,
+            Self::Return(x) => x.span,
+            Self::Class(x) => x.span,
+            Self::Break(x) => x.span,
+            Self::Continue(x) => x.span,
+            Self::Throw(x) => x.span,
+            Self::Try(x) => x.span,
+        }
+    }
+}
+
+fn join(statements: &[Stmt]) -> String {
+    statements
+        .iter()
+        .map(|stmt| stmt.to_string())
+        .collect::<Vec<_>>()
+        .join(" ")
 }
 
 impl Display for Stmt {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
-            Self::Expression(x) => write!(f, "{}", x.expression),
-            Self::Print(x) => write!(f, "{}", x.expression),
-            Self::Var(x) => {
-                if let Some(y) = x.initializer.clone() {
-                    write!(f, "{}", y)
-                } else {
-                    write!(f, "")
+            Self::Expression(x) => write!(f, "Expr({})", x.expression),
+            Self::Print(x) => write!(f, "Print({})", x.expression),
+            Self::Var(x) => match &x.initializer {
+                Some(y) => write!(f, "Var({} = {})", x.name.lexeme, y),
+                None => write!(f, "Var({})", x.name.lexeme),
+            },
+            Self::Block(x) => write!(f, "Block({})", join(&x.statements)),
+            Self::If(x) => match &*x.else_branch {
+                Some(y) => write!(f, "If({} {} else {})", x.condition, x.then_branch, y),
+                None => write!(f, "If({} {})", x.condition, x.then_branch),
+            },
+            Self::While(x) => match &x.increment {
+                Some(y) => write!(f, "While({} {} then {})", x.condition, x.body, y),
+                None => write!(f, "While({} {})", x.condition, x.body),
+            },
+            Self::Fun(x) => write!(
+                f,
+                "Fun({}({}) {})",
+                x.name.lexeme,
+                x.params
+                    .iter()
+                    .map(|param| param.lexeme.clone())
+                    .chain(x.rest.iter().map(|rest| format!("...{}", rest.lexeme)))
+                    .collect::<Vec<_>>()
+                    .join(", "),
+                join(&x.body)
+            ),
+            Self::Return(x) => match &*x.value {
+                Some(y) => write!(f, "Return({})", y),
+                None => write!(f, "Return"),
+            },
+            Self::Class(x) => match &*x.superclass {
+                Some(y) => write!(f, "Class({} < {} {})", x.name.lexeme, y, join(&x.methods)),
+                None => write!(f, "Class({} {})", x.name.lexeme, join(&x.methods)),
+            },
+            Self::Break(_) => write!(f, "Break"),
+            Self::Continue(_) => write!(f, "Continue"),
+            Self::Throw(x) => write!(f, "Throw({})", x.value),
+            Self::Try(x) => {
+                write!(f, "Try({}", join(&x.body))?;
+                if let Some(catch) = &x.catch {
+                    write!(f, " catch {} ({})", catch.name.lexeme, join(&catch.body))?;
                 }
-            } // TODO: This is wrong, fix it
-            Self::Block(x) => {
-                write!(f, "{:?}", x)
-            }
-            Self::If(x) => {
-                write!(f, "{:?}", x)
-            }
-            Self::While(x) => {
-                write!(f, "{:?}", x)
-            }
-            Self::Fun(x) => {
-                write!(f, "{:?}", x)
-            }
-            Self::Return(x) => {
-                write!(f, "{:?}", x)
-            }
-            Self::Class(x) => {
-                write!(f, "{:?}", x)
+                if let Some(finally) = &x.finally {
+                    write!(f, " finally ({})", join(finally))?;
+                }
+                write!(f, ")")
             }
         }
     }
@@ -72,16 +129,17 @@ impl Display for Stmt {
 #[derive(Clone, Debug)]
 pub struct ExpressionStmt {
     pub expression: Expr,
+    pub span: Span,
 }
 
 impl ExpressionStmt {
-    pub fn new(expression: Expr) -> Self {
-        ExpressionStmt { expression }
+    pub fn new(expression: Expr, span: Span) -> Self {
+        ExpressionStmt { expression, span }
     }
 }
 
 impl<T> StmtVisitorAcceptor<T> for ExpressionStmt {
-    fn accept(&self, visitor: &mut impl StmtVisitor<T>) -> Result<T, Return> {
+    fn accept(&self, visitor: &mut impl StmtVisitor<T>) -> Result<T, Unwind> {
         visitor.visit_expression_stmt(self.clone())
     }
 }
@@ -89,16 +147,17 @@ impl<T> StmtVisitorAcceptor<T> for ExpressionStmt {
 #[derive(Clone, Debug)]
 pub struct PrintStmt {
     pub expression: Expr,
+    pub span: Span,
 }
 
 impl PrintStmt {
-    pub fn new(expression: Expr) -> Self {
-        PrintStmt { expression }
+    pub fn new(expression: Expr, span: Span) -> Self {
+        PrintStmt { expression, span }
     }
 }
 
 impl<T> StmtVisitorAcceptor<T> for PrintStmt {
-    fn accept(&self, visitor: &mut impl StmtVisitor<T>) -> Result<T, Return> {
+    fn accept(&self, visitor: &mut impl StmtVisitor<T>) -> Result<T, Unwind> {
         visitor.visit_print_stmt(self.clone())
     }
 }
@@ -107,25 +166,28 @@ impl<T> StmtVisitorAcceptor<T> for PrintStmt {
 pub struct VarStmt {
     pub name: Token,
     pub initializer: Option<Expr>,
+    pub span: Span,
 }
 
 impl VarStmt {
-    pub fn new(name: Token, initializer: Option<Expr>) -> Self {
+    pub fn new(name: Token, initializer: Option<Expr>, span: Span) -> Self {
         match initializer {
             Some(x) => VarStmt {
                 name,
                 initializer: Some(x),
+                span,
             },
             None => VarStmt {
                 name,
                 initializer: None,
+                span,
             },
         }
     }
 }
 
 impl<T> StmtVisitorAcceptor<T> for VarStmt {
-    fn accept(&self, visitor: &mut impl StmtVisitor<T>) -> Result<T, Return> {
+    fn accept(&self, visitor: &mut impl StmtVisitor<T>) -> Result<T, Unwind> {
         visitor.visit_var_stmt(self.clone())
     }
 }
@@ -133,16 +195,17 @@ impl<T> StmtVisitorAcceptor<T> for VarStmt {
 #[derive(Clone, Debug)]
 pub struct BlockStmt {
     pub statements: Vec<Stmt>,
+    pub span: Span,
 }
 
 impl BlockStmt {
-    pub fn new(statements: Vec<Stmt>) -> Self {
-        BlockStmt { statements }
+    pub fn new(statements: Vec<Stmt>, span: Span) -> Self {
+        BlockStmt { statements, span }
     }
 }
 
 impl<T> StmtVisitorAcceptor<T> for BlockStmt {
-    fn accept(&self, visitor: &mut impl StmtVisitor<T>) -> Result<T, Return> {
+    fn accept(&self, visitor: &mut impl StmtVisitor<T>) -> Result<T, Unwind> {
         visitor.visit_block_stmt(self.clone())
     }
 }
@@ -152,20 +215,22 @@ pub struct IfStmt {
     pub condition: Expr,
     pub then_branch: Box<Stmt>,
     pub else_branch: Box<Option<Stmt>>,
+    pub span: Span,
 }
 
 impl IfStmt {
-    pub fn new(condition: Expr, then_branch: Stmt, else_branch: Option<Stmt>) -> Self {
+    pub fn new(condition: Expr, then_branch: Stmt, else_branch: Option<Stmt>, span: Span) -> Self {
         IfStmt {
             condition,
             then_branch: Box::new(then_branch),
             else_branch: Box::new(else_branch),
+            span,
         }
     }
 }
 
 impl<T> StmtVisitorAcceptor<T> for IfStmt {
-    fn accept(&self, visitor: &mut impl StmtVisitor<T>) -> Result<T, Return> {
+    fn accept(&self, visitor: &mut impl StmtVisitor<T>) -> Result<T, Unwind> {
         visitor.visit_if_stmt(self.clone())
     }
 }
@@ -174,19 +239,24 @@ impl<T> StmtVisitorAcceptor<T> for IfStmt {
 pub struct WhileStmt {
     pub condition: Expr,
     pub body: Box<Stmt>,
+    /// The increment of a `for` loop, run after the body on every iteration.
+    pub increment: Option<Expr>,
+    pub span: Span,
 }
 
 impl WhileStmt {
-    pub fn new(condition: Expr, body: Stmt) -> Self {
+    pub fn new(condition: Expr, body: Stmt, increment: Option<Expr>, span: Span) -> Self {
         WhileStmt {
             condition,
             body: Box::new(body),
+            increment,
+            span,
         }
     }
 }
 
 impl<T> StmtVisitorAcceptor<T> for WhileStmt {
-    fn accept(&self, visitor: &mut impl StmtVisitor<T>) -> Result<T, Return> {
+    fn accept(&self, visitor: &mut impl StmtVisitor<T>) -> Result<T, Unwind> {
         visitor.visit_while_stmt(self.clone())
     }
 }
@@ -195,17 +265,36 @@ impl<T> StmtVisitorAcceptor<T> for WhileStmt {
 pub struct FunStmt {
     pub name: Token,
     pub params: Vec<Token>,
+    /// The `...name` parameter collecting any further arguments into a list.
+    pub rest: Option<Token>,
     pub body: Vec<Stmt>,
+    /// Text of the `///` comments right before the function.
+    pub doc: Option<String>,
+    pub span: Span,
 }
 
 impl FunStmt {
-    pub fn new(name: Token, params: Vec<Token>, body: Vec<Stmt>) -> Self {
-        FunStmt { name, params, body }
+    pub fn new(
+        name: Token,
+        params: Vec<Token>,
+        rest: Option<Token>,
+        body: Vec<Stmt>,
+        doc: Option<String>,
+        span: Span,
+    ) -> Self {
+        FunStmt {
+            name,
+            params,
+            rest,
+            body,
+            doc,
+            span,
+        }
     }
 }
 
 impl<T> StmtVisitorAcceptor<T> for FunStmt {
-    fn accept(&self, visitor: &mut impl StmtVisitor<T>) -> Result<T, Return> {
+    fn accept(&self, visitor: &mut impl StmtVisitor<T>) -> Result<T, Unwind> {
         visitor.visit_fun_stmt(self.clone())
     }
 }
@@ -214,19 +303,21 @@ impl<T> StmtVisitorAcceptor<T> for FunStmt {
 pub struct ReturnStmt {
     pub keyword: Token,
     pub value: Box<Option<Expr>>,
+    pub span: Span,
 }
 
 impl ReturnStmt {
-    pub fn new(keyword: Token, value: Option<Expr>) -> Self {
+    pub fn new(keyword: Token, value: Option<Expr>, span: Span) -> Self {
         ReturnStmt {
             keyword,
             value: Box::new(value),
+            span,
         }
     }
 }
 
 impl<T> StmtVisitorAcceptor<T> for ReturnStmt {
-    fn accept(&self, visitor: &mut impl StmtVisitor<T>) -> Result<T, Return> {
+    fn accept(&self, visitor: &mut impl StmtVisitor<T>) -> Result<T, Unwind> {
         let output = visitor.visit_return_stmt(self.clone())?;
         Ok(output)
     }
@@ -237,21 +328,128 @@ pub struct ClassStmt {
     pub name: Token,
     pub superclass: Box<Option<Expr>>,
     pub methods: Vec<Stmt>,
+    /// Text of the `///` comments right before the class.
+    pub doc: Option<String>,
+    pub span: Span,
 }
 
 impl ClassStmt {
-    pub fn new(name: Token, superclass: Option<Expr>, methods: Vec<Stmt>) -> Self {
+    pub fn new(
+        name: Token,
+        superclass: Option<Expr>,
+        methods: Vec<Stmt>,
+        doc: Option<String>,
+        span: Span,
+    ) -> Self {
         ClassStmt {
             name,
             superclass: Box::new(superclass),
             methods,
+            doc,
+            span,
         }
     }
 }
 
 impl<T> StmtVisitorAcceptor<T> for ClassStmt {
-    fn accept(&self, visitor: &mut impl StmtVisitor<T>) -> Result<T, Return> {
+    fn accept(&self, visitor: &mut impl StmtVisitor<T>) -> Result<T, Unwind> {
         let output = visitor.visit_class_stmt(self.clone())?;
         Ok(output)
     }
 }
+
+#[derive(Clone, Debug)]
+pub struct BreakStmt {
+    pub keyword: Token,
+    pub span: Span,
+}
+
+impl BreakStmt {
+    pub fn new(keyword: Token, span: Span) -> Self {
+        BreakStmt { keyword, span }
+    }
+}
+
+impl<T> StmtVisitorAcceptor<T> for BreakStmt {
+    fn accept(&self, visitor: &mut impl StmtVisitor<T>) -> Result<T, Unwind> {
+        visitor.visit_break_stmt(self.clone())
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct ContinueStmt {
+    pub keyword: Token,
+    pub span: Span,
+}
+
+impl ContinueStmt {
+    pub fn new(keyword: Token, span: Span) -> Self {
+        ContinueStmt { keyword, span }
+    }
+}
+
+impl<T> StmtVisitorAcceptor<T> for ContinueStmt {
+    fn accept(&self, visitor: &mut impl StmtVisitor<T>) -> Result<T, Unwind> {
+        visitor.visit_continue_stmt(self.clone())
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct ThrowStmt {
+    pub keyword: Token,
+    pub value: Expr,
+    pub span: Span,
+}
+
+impl ThrowStmt {
+    pub fn new(keyword: Token, value: Expr, span: Span) -> Self {
+        ThrowStmt {
+            keyword,
+            value,
+            span,
+        }
+    }
+}
+
+impl<T> StmtVisitorAcceptor<T> for ThrowStmt {
+    fn accept(&self, visitor: &mut impl StmtVisitor<T>) -> Result<T, Unwind> {
+        visitor.visit_throw_stmt(self.clone())
+    }
+}
+
+/// The `catch (name) { ... }` part of a `try` statement.
+#[derive(Clone, Debug)]
+pub struct CatchClause {
+    pub name: Token,
+    pub body: Vec<Stmt>,
+}
+
+#[derive(Clone, Debug)]
+pub struct TryStmt {
+    pub body: Vec<Stmt>,
+    pub catch: Option<CatchClause>,
+    pub finally: Option<Vec<Stmt>>,
+    pub span: Span,
+}
+
+impl TryStmt {
+    pub fn new(
+        body: Vec<Stmt>,
+        catch: Option<CatchClause>,
+        finally: Option<Vec<Stmt>>,
+        span: Span,
+    ) -> Self {
+        TryStmt {
+            body,
+            catch,
+            finally,
+            span,
+        }
+    }
+}
+
+impl<T> StmtVisitorAcceptor<T> for TryStmt {
+    fn accept(&self, visitor: &mut impl StmtVisitor<T>) -> Result<T, Unwind> {
+        visitor.visit_try_stmt(self.clone())
+    }
+}
@@ -2,3 +2,10 @@ pub fn compare_output(expected: &str, source: &str) {
     let output = crate::run(source);
     assert_eq!(expected, output);
 }
+
+pub fn compare_error(expected: &str, source: &str) {
+    match crate::try_run(source) {
+        Ok(output) => panic!("Expected error '{expected}', got output '{output}'"),
+        Err(error) => assert_eq!(expected, error.to_string()),
+    }
+}
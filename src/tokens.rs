@@ -1,10 +1,46 @@
 use std::{
+    cell::RefCell,
     cmp::Ordering,
     fmt::Display,
-    ops::{Add, Div, Mul, Sub},
+    hash::{Hash, Hasher},
+    ops::{Add, Div, Mul, Rem, Sub},
+    rc::Rc,
 };
 
-use crate::{callable::LoxFunction, class::LoxClass, instance::LoxInstance};
+use crate::{
+    bigint::BigInt,
+    callable::{LoxCallable, LoxFunction},
+    class::LoxClass,
+    instance::LoxInstance,
+    map::LoxMap,
+};
+
+/// Byte range of a piece of source code, `end` is exclusive.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Span {
+    pub start: usize,
+    pub end: usize,
+}
+
+impl Span {
+    pub fn new(start: usize, end: usize) -> Self {
+        Span { start, end }
+    }
+
+    /// Smallest span covering both `self` and `other`.
+    pub fn to(self, other: Span) -> Self {
+        Span {
+            start: self.start.min(other.start),
+            end: self.end.max(other.end),
+        }
+    }
+}
+
+impl Display for Span {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}..{}", self.start, self.end)
+    }
+}
 
 #[derive(Debug, Clone, PartialEq)]
 pub struct Token {
@@ -12,7 +48,7 @@ pub struct Token {
     pub lexeme: String,
     pub literal: LoxObject,
     pub line: usize,
-    pub current: usize,
+    pub span: Span,
 }
 
 impl Token {
@@ -21,14 +57,14 @@ impl Token {
         lexeme: String,
         literal: LoxObject,
         line: usize,
-        current: usize,
+        span: Span,
     ) -> Self {
         Token {
             token_type,
             lexeme,
             literal,
             line,
-            current,
+            span,
         }
     }
 }
@@ -38,7 +74,7 @@ impl Display for Token {
         write!(
             f,
             "{} {} {:?} {} {}",
-            self.line, self.current, self.token_type, self.lexeme, self.literal
+            self.line, self.span, self.token_type, self.lexeme, self.literal
         )
     }
 }
@@ -46,25 +82,42 @@ impl Display for Token {
 #[derive(Debug)]
 pub enum LoxObject {
     String(String),
-    Number(f32),
+    Int(i64),
+    /// An integer too large for `Int`, integer arithmetic overflows into it.
+    BigInt(BigInt),
+    Number(f64),
     Bool(bool),
     None,
     FunCall(Box<LoxFunction>),
     Class(LoxClass),
     Instance(LoxInstance),
+    List(Rc<RefCell<Vec<LoxObject>>>),
+    Map(Rc<RefCell<LoxMap>>),
+    /// A callable implemented in Rust, like `clock`.
+    Native(Rc<dyn LoxCallable>),
 }
 
 impl PartialOrd for LoxObject {
     fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
         match (self, other) {
             (Self::String(l), Self::String(r)) => l.partial_cmp(r),
-            (Self::Number(l), Self::Number(r)) => l.partial_cmp(r),
+            (Self::Int(l), Self::Int(r)) => l.partial_cmp(r),
+            (Self::Int(_) | Self::BigInt(_), Self::Int(_) | Self::BigInt(_)) => {
+                self.as_bigint().partial_cmp(&other.as_bigint())
+            }
+            // Exactly, like `==`
+            (Self::Int(_) | Self::BigInt(_), Self::Number(x)) => {
+                compare_with_float(&self.as_bigint()?, *x)
+            }
+            (Self::Number(x), Self::Int(_) | Self::BigInt(_)) => {
+                compare_with_float(&other.as_bigint()?, *x).map(Ordering::reverse)
+            }
             (Self::Bool(l), Self::Bool(r)) => l.partial_cmp(r),
             (Self::FunCall(_), Self::FunCall(_)) => None,
             (Self::None, Self::None) => Some(Ordering::Equal),
             (Self::None, _) => Some(Ordering::Less),
             (_, Self::None) => Some(Ordering::Greater),
-            (_, _) => None,
+            (l, r) => l.as_float()?.partial_cmp(&r.as_float()?),
         }
     }
 }
@@ -73,10 +126,57 @@ impl PartialEq for LoxObject {
     fn eq(&self, other: &Self) -> bool {
         match (self, other) {
             (Self::String(l0), Self::String(r0)) => l0 == r0,
+            (Self::Int(l0), Self::Int(r0)) => l0 == r0,
+            (Self::Int(_) | Self::BigInt(_), Self::Int(_) | Self::BigInt(_)) => {
+                self.as_bigint() == other.as_bigint()
+            }
             (Self::Number(l0), Self::Number(r0)) => l0 == r0,
+            // Exactly, not after rounding the integer to a float
+            (Self::Number(x), Self::Int(_) | Self::BigInt(_)) => integral(*x) == other.as_bigint(),
+            (Self::Int(_) | Self::BigInt(_), Self::Number(x)) => self.as_bigint() == integral(*x),
             (Self::Bool(l0), Self::Bool(r0)) => l0 == r0,
-            (Self::FunCall(_l0), Self::FunCall(_r0)) => false,
-            _ => core::mem::discriminant(self) == core::mem::discriminant(other),
+            (Self::None, Self::None) => true,
+            // Everything else is equal only to itself
+            (Self::FunCall(l0), Self::FunCall(r0)) => Rc::ptr_eq(&l0.id, &r0.id),
+            (Self::Class(l0), Self::Class(r0)) => l0 == r0,
+            (Self::Instance(l0), Self::Instance(r0)) => Rc::ptr_eq(&l0.fields, &r0.fields),
+            (Self::List(l0), Self::List(r0)) => Rc::ptr_eq(l0, r0),
+            (Self::Map(l0), Self::Map(r0)) => Rc::ptr_eq(l0, r0),
+            (Self::Native(l0), Self::Native(r0)) => Rc::ptr_eq(l0, r0),
+            _ => false,
+        }
+    }
+}
+
+/// Consistent with `PartialEq`: integral numbers of every kind hash by their
+/// value as an integer, other floats by their bits, and the values that are only
+/// equal to themselves hash by identity.
+impl Hash for LoxObject {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        match self {
+            Self::String(x) => (0, x).hash(state),
+            Self::Int(x) => (1, x).hash(state),
+            Self::BigInt(_) | Self::Number(_) => {
+                let integer = match self {
+                    Self::Number(x) => integral(*x),
+                    _ => self.as_bigint(),
+                };
+                match integer {
+                    Some(x) => match x.to_i64() {
+                        Some(x) => (1, x).hash(state),
+                        None => (1, x).hash(state),
+                    },
+                    None => (1, self.as_float().unwrap_or_default().to_bits()).hash(state),
+                }
+            }
+            Self::Bool(x) => (2, x).hash(state),
+            Self::None => 3.hash(state),
+            Self::FunCall(x) => (4, Rc::as_ptr(&x.id)).hash(state),
+            Self::Class(x) => (5, Rc::as_ptr(&x.id)).hash(state),
+            Self::Instance(x) => (6, Rc::as_ptr(&x.fields)).hash(state),
+            Self::List(x) => (7, Rc::as_ptr(x)).hash(state),
+            Self::Map(x) => (8, Rc::as_ptr(x)).hash(state),
+            Self::Native(x) => (9, Rc::as_ptr(x) as *const ()).hash(state),
         }
     }
 }
@@ -85,55 +185,182 @@ impl Clone for LoxObject {
     fn clone(&self) -> Self {
         match &self {
             Self::String(x) => LoxObject::String(x.clone()),
+            Self::Int(x) => LoxObject::Int(*x),
+            Self::BigInt(x) => LoxObject::BigInt(x.clone()),
             Self::Number(x) => LoxObject::Number(*x),
             Self::Bool(x) => LoxObject::Bool(*x),
             Self::None => LoxObject::None,
             Self::FunCall(x) => LoxObject::FunCall(Box::new(*x.clone())),
             Self::Class(x) => LoxObject::Class(x.clone()),
             Self::Instance(x) => LoxObject::Instance(x.clone()),
+            Self::List(x) => LoxObject::List(Rc::clone(x)),
+            Self::Map(x) => LoxObject::Map(Rc::clone(x)),
+            Self::Native(x) => LoxObject::Native(Rc::clone(x)),
+        }
+    }
+}
+
+impl LoxObject {
+    /// The value of any number as a float, for mixed arithmetic.
+    pub fn as_float(&self) -> Option<f64> {
+        match self {
+            LoxObject::Int(x) => Some(*x as f64),
+            LoxObject::BigInt(x) => Some(x.to_f64()),
+            LoxObject::Number(x) => Some(*x),
+            _ => None,
+        }
+    }
+
+    /// The value of an `Int` or `BigInt` as a `BigInt`.
+    pub fn as_bigint(&self) -> Option<BigInt> {
+        match self {
+            LoxObject::Int(x) => Some(BigInt::from(*x)),
+            LoxObject::BigInt(x) => Some(x.clone()),
+            _ => None,
+        }
+    }
+
+    /// An `Int` if `value` fits in one, otherwise a `BigInt`.
+    pub fn integer(value: BigInt) -> LoxObject {
+        match value.to_i64() {
+            Some(value) => LoxObject::Int(value),
+            None => LoxObject::BigInt(value),
         }
     }
 }
 
+/// The value of `x` as an integer, if it has no fractional part.
+fn integral(x: f64) -> Option<BigInt> {
+    if x.fract() == 0.0 {
+        BigInt::from_f64(x)
+    } else {
+        None
+    }
+}
+
+/// Orders an integer against `x` without rounding the integer to a float: by the
+/// integral part of `x` first, then by its fractional part.
+fn compare_with_float(int: &BigInt, x: f64) -> Option<Ordering> {
+    if x.is_infinite() {
+        return Some(if x > 0.0 {
+            Ordering::Less
+        } else {
+            Ordering::Greater
+        });
+    }
+    let whole = BigInt::from_f64(x)?;
+    Some(int.cmp(&whole).then(0.0.partial_cmp(&x.fract())?))
+}
+
+/// Applies `int` to two integers, retrying with `big` when it overflows or when
+/// either is already a `BigInt`, and `float` to any other pair of numbers.
+/// Results of `big` that fit in an `Int` become one again.
+fn arithmetic(
+    lhs: LoxObject,
+    rhs: LoxObject,
+    int: fn(i64, i64) -> Option<i64>,
+    big: fn(&BigInt, &BigInt) -> BigInt,
+    float: fn(f64, f64) -> f64,
+) -> Result<LoxObject, String> {
+    if let (LoxObject::Int(x), LoxObject::Int(y)) = (&lhs, &rhs) {
+        if let Some(value) = int(*x, *y) {
+            return Ok(LoxObject::Int(value));
+        }
+    }
+    if let (Some(x), Some(y)) = (lhs.as_bigint(), rhs.as_bigint()) {
+        return Ok(LoxObject::integer(big(&x, &y)));
+    }
+    match (lhs.as_float(), rhs.as_float()) {
+        (Some(x), Some(y)) => Ok(LoxObject::Number(float(x, y))),
+        _ => Err("Operands must be numbers.".to_string()),
+    }
+}
+
 impl Add for LoxObject {
-    type Output = LoxObject;
+    type Output = Result<LoxObject, String>;
 
     fn add(self, rhs: Self) -> Self::Output {
         match (self, rhs) {
-            (LoxObject::Number(x), LoxObject::Number(y)) => LoxObject::Number(x + y),
-            (LoxObject::String(x), LoxObject::String(y)) => LoxObject::String(x + &y),
-            _ => panic!(),
+            (LoxObject::String(x), LoxObject::String(y)) => Ok(LoxObject::String(x + &y)),
+            (x, y) if x.as_float().is_some() && y.as_float().is_some() => {
+                arithmetic(x, y, i64::checked_add, BigInt::add, |x, y| x + y)
+            }
+            _ => Err("Operands must be two numbers or two strings.".to_string()),
         }
     }
 }
 
 impl Sub for LoxObject {
-    type Output = LoxObject;
+    type Output = Result<LoxObject, String>;
 
     fn sub(self, rhs: Self) -> Self::Output {
-        match (self, rhs) {
-            (LoxObject::Number(x), LoxObject::Number(y)) => LoxObject::Number(x - y),
-            _ => panic!(),
-        }
+        arithmetic(self, rhs, i64::checked_sub, BigInt::sub, |x, y| x - y)
     }
 }
 
 impl Mul for LoxObject {
-    type Output = LoxObject;
+    type Output = Result<LoxObject, String>;
     fn mul(self, rhs: Self) -> Self::Output {
-        match (self, rhs) {
-            (LoxObject::Number(x), LoxObject::Number(y)) => LoxObject::Number(x * y),
-            _ => panic!(),
-        }
+        arithmetic(self, rhs, i64::checked_mul, BigInt::mul, |x, y| x * y)
     }
 }
 
+/// Dividing two integers truncates towards zero, like `i64` division does.
 impl Div for LoxObject {
-    type Output = LoxObject;
+    type Output = Result<LoxObject, String>;
     fn div(self, rhs: Self) -> Self::Output {
-        match (self, rhs) {
-            (LoxObject::Number(x), LoxObject::Number(y)) => LoxObject::Number(x / y),
-            _ => panic!(),
+        if self.as_bigint().is_some() && rhs.as_bigint().is_some_and(|y| y.is_zero()) {
+            return Err("Division by zero.".to_string());
+        }
+        arithmetic(
+            self,
+            rhs,
+            i64::checked_div,
+            |x, y| x.div_rem(y).unwrap_or_default().0,
+            |x, y| x / y,
+        )
+    }
+}
+
+/// The remainder takes the sign of the dividend, so that `x == (x / y) * y + x % y`
+/// holds for integers.
+impl Rem for LoxObject {
+    type Output = Result<LoxObject, String>;
+    fn rem(self, rhs: Self) -> Self::Output {
+        if self.as_bigint().is_some() && rhs.as_bigint().is_some_and(|y| y.is_zero()) {
+            return Err("Division by zero.".to_string());
+        }
+        arithmetic(
+            self,
+            rhs,
+            i64::checked_rem,
+            |x, y| x.div_rem(y).unwrap_or_default().1,
+            |x, y| x % y,
+        )
+    }
+}
+
+/// Formats numbers the way jlox does, with Java's `Double.toString`: plain
+/// decimals from 0.001 up to 10 million, without a trailing `.0`, and
+/// scientific notation like `1.0E21` outside of that range. The digits are the
+/// shortest that read back as the same value.
+pub fn format_number(number: f64) -> String {
+    if number.is_infinite() {
+        if number > 0.0 {
+            "Infinity"
+        } else {
+            "-Infinity"
+        }
+        .to_string()
+    } else if number.is_nan() || number == 0.0 || (1e-3..1e7).contains(&number.abs()) {
+        number.to_string()
+    } else {
+        let text = format!("{:e}", number);
+        let (mantissa, exponent) = text.split_once('e').unwrap_or((&text, "0"));
+        if mantissa.contains('.') {
+            format!("{}E{}", mantissa, exponent)
+        } else {
+            format!("{}.0E{}", mantissa, exponent)
         }
     }
 }
@@ -143,11 +370,64 @@ impl Display for LoxObject {
         match self {
             LoxObject::None => write!(f, "nil"),
             LoxObject::Bool(x) => write!(f, "{}", x),
-            LoxObject::Number(x) => write!(f, "{}", x),
+            LoxObject::Int(x) => write!(f, "{}", x),
+            LoxObject::BigInt(x) => write!(f, "{}", x),
+            LoxObject::Number(x) => write!(f, "{}", format_number(*x)),
             LoxObject::String(x) => write!(f, "{}", x),
             LoxObject::FunCall(_x) => write!(f, "<loxFunction>"),
             LoxObject::Class(x) => write!(f, "<loxClass {}>", x.name),
             LoxObject::Instance(x) => write!(f, "<loxInstance {}>", x.class.name),
+            LoxObject::Native(x) => write!(f, "{}", x.to_string()),
+            LoxObject::List(_) | LoxObject::Map(_) => self.write_nested(f, &mut Vec::new()),
+        }
+    }
+}
+
+impl LoxObject {
+    /// Writes a list or map and what it contains. `open` holds the lists and maps
+    /// already being written further out, any of them found again is written as
+    /// `[...]` or `{...}` instead of recursing forever.
+    fn write_nested(
+        &self,
+        f: &mut std::fmt::Formatter<'_>,
+        open: &mut Vec<*const ()>,
+    ) -> std::fmt::Result {
+        match self {
+            LoxObject::List(x) => {
+                let pointer = Rc::as_ptr(x) as *const ();
+                if open.contains(&pointer) {
+                    return write!(f, "[...]");
+                }
+                open.push(pointer);
+                write!(f, "[")?;
+                for (i, item) in x.borrow().iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    item.write_nested(f, open)?;
+                }
+                open.pop();
+                write!(f, "]")
+            }
+            LoxObject::Map(x) => {
+                let pointer = Rc::as_ptr(x) as *const ();
+                if open.contains(&pointer) {
+                    return write!(f, "{{...}}");
+                }
+                open.push(pointer);
+                write!(f, "{{")?;
+                for (i, (key, value)) in x.borrow().iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    key.write_nested(f, open)?;
+                    write!(f, ": ")?;
+                    value.write_nested(f, open)?;
+                }
+                open.pop();
+                write!(f, "}}")
+            }
+            _ => write!(f, "{}", self),
         }
     }
 }
@@ -160,13 +440,18 @@ pub enum TokenType {
     RightParen,
     LeftBrace,
     RightBrace,
+    LeftBracket,
+    RightBracket,
+    Colon,
     Comma,
     Dot,
+    Ellipsis,
     Minus,
     Plus,
     Semicolon,
     Slash,
     Star,
+    Percent,
 
     // One or two character tokens
     Bang,
@@ -181,13 +466,21 @@ pub enum TokenType {
     // Literals
     Identifier,
     String,
+    /// Part of a string literal up to a `${`, the interpolated expression follows.
+    Interpolation,
+    /// A `///` comment, its text is the literal.
+    DocComment,
     Number,
 
     // Keywords
     And,
+    Break,
+    Catch,
     Class,
+    Continue,
     Else,
     False,
+    Finally,
     Fun,
     For,
     If,
@@ -197,7 +490,9 @@ pub enum TokenType {
     Return,
     Super,
     This,
+    Throw,
     True,
+    Try,
     Var,
     While,
 
@@ -64,4 +64,50 @@ mod test_classes {
              var foo = Foo();",
         );
     }
+
+    #[test]
+    fn test_class_init_early_return() {
+        compare_output(
+            "<loxInstance Foo>\n",
+            "class Foo {
+                init() {
+                    return;
+                    print \"unreachable\";
+                }
+             }
+             print Foo();",
+        );
+    }
+
+    #[test]
+    fn test_equality_is_identity() {
+        compare_output(
+            "true\nfalse\ntrue\nfalse\ntrue\nfalse\nfalse\n",
+            "class Foo { get() { return 1; } }
+             class Bar {}
+             var a = Foo();
+             var b = Foo();
+             var c = a;
+             print a == c;
+             print a == b;
+             print Foo == Foo;
+             print Foo == Bar;
+             fun f() {}
+             fun g() {}
+             print f == f;
+             print a.get == b.get;
+             print f == g;",
+        );
+        compare_output(
+            "false\ntrue\nfirst\n",
+            "fun make() { class A {} return A; }
+             var first = make();
+             print first == make();
+             print first == first;
+             var m = {};
+             m[first] = \"first\";
+             m[make()] = \"second\";
+             print m[first];",
+        );
+    }
 }
@@ -0,0 +1,393 @@
+#[cfg(test)]
+mod test_errors {
+    use lox::{
+        exceptions::LoxError, interpreter::MAX_FRAMES, logger::render_error,
+        testing_utils::compare_error, try_run,
+    };
+
+    fn rendered(source: &str) -> String {
+        render_error(source, &try_run(source).unwrap_err())
+    }
+
+    #[test]
+    fn test_arithmetic_type_errors() {
+        compare_error("Operands must be numbers. [line 1]", "print 1 - \"a\";");
+        compare_error(
+            "Operands must be two numbers or two strings. [line 2]",
+            "var a = 1;
+             print a + \"b\";",
+        );
+        compare_error("Operand must be a number. [line 1]", "print -\"a\";");
+        compare_error(
+            "Operands must be two numbers or two strings. [line 1]",
+            "print 1 < true;",
+        );
+    }
+
+    #[test]
+    fn test_undefined_variable() {
+        compare_error("Undefined variable 'a'. [line 1]", "print a;");
+        compare_error("Undefined variable 'a'. [line 1]", "a = 1;");
+    }
+
+    #[test]
+    fn test_call_errors() {
+        compare_error(
+            "Can only call functions and classes. [line 1]",
+            "\"not a function\"();",
+        );
+        compare_error(
+            "Expected 2 arguments but got 1. [line 2]",
+            "fun add(a, b) { return a + b; }
+             add(1);",
+        );
+    }
+
+    #[test]
+    fn test_property_errors() {
+        compare_error(
+            "Only instances have properties. [line 1]",
+            "print true.field;",
+        );
+        compare_error(
+            "Undefined property 'missing'. [line 2]",
+            "class Bagel {}
+             print Bagel().missing;",
+        );
+        compare_error(
+            "Superclass must be a class. [line 2]",
+            "var NotAClass = 1;
+             class Bagel < NotAClass {}",
+        );
+    }
+
+    #[test]
+    fn test_error_inside_function() {
+        compare_error(
+            "Operands must be numbers. [line 2]",
+            "fun broken() {
+                return nil * 2;
+             }
+             broken();",
+        );
+    }
+
+    #[test]
+    fn test_parse_error_locations() {
+        compare_error("[line 1] Error at end: Expect ';' after value.", "print 1");
+        compare_error("[line 1] Error at ')': Expect expression.", "print (1 + );");
+        compare_error(
+            "[line 1] Error at '=': Invalid assignment target.",
+            "1 + 2 = 3;",
+        );
+    }
+
+    #[test]
+    fn test_parse_errors_are_collected() {
+        compare_error(
+            "[line 1] Error at '2': Expect ';' after value.\n\
+             [line 3] Error at ';': Expect variable name.",
+            "print 1 2;
+             print 3;
+             var ;",
+        );
+    }
+
+    #[test]
+    fn test_invalid_escapes() {
+        compare_error(
+            "[line 2] Error: Invalid escape sequence '\\q'.\n\
+             [line 3] Error: Invalid unicode escape.\n\
+             [line 3] Error: Expect '}' after unicode escape.",
+            r#"print "fine\n";
+               print "bad \q
+               x \u{110000} \u{zz}";"#,
+        );
+    }
+
+    #[test]
+    fn test_block_comment_errors() {
+        compare_error(
+            "Operands must be numbers. [line 3]",
+            "/* line 1
+                line 2 */
+             print 1 - nil;",
+        );
+        compare_error(
+            "[line 2] Error: Unterminated block comment.",
+            "/* /* */
+             print 1;",
+        );
+    }
+
+    #[test]
+    fn test_malformed_numbers() {
+        compare_error(
+            "[line 1] Error: Expect digits after '0x'.\n\
+             [line 1] Error: Invalid digit in binary number.\n\
+             [line 2] Error: Malformed number.\n\
+             [line 2] Error: Malformed number.\n\
+             [line 3] Error: Malformed number.\n\
+             [line 3] Error: Invalid digit in octal number.",
+            "print 0x; print 0b102;
+             print 1__0; print 1_;
+             print 12abc; print 0o777777777777777777777778;",
+        );
+    }
+
+    #[test]
+    fn test_integer_errors() {
+        compare_error("Division by zero. [line 1]", "print 1 / 0;");
+        compare_error("Division by zero. [line 1]", "print 1 % 0;");
+        compare_error(
+            "Division by zero. [line 1]",
+            "print 99999999999999999999 / 0;",
+        );
+        compare_error(
+            "Number is too large for an integer. [line 1]",
+            "print 99999999999999999999.toInt();",
+        );
+        compare_error("Undefined property 'nope'. [line 1]", "print 1.nope();");
+        compare_error(
+            "Expected 0 arguments but got 1. [line 1]",
+            "print 1.toFloat(2);",
+        );
+        compare_error(
+            "Only instances have properties. [line 1]",
+            "print \"a\".toInt();",
+        );
+    }
+
+    #[test]
+    fn test_rest_parameter_errors() {
+        compare_error(
+            "[line 1] Error at ',': Rest parameter must be last.",
+            "fun f(...a, b) {}",
+        );
+        compare_error(
+            "Expected at least 1 argument but got 0. [line 2]",
+            "fun f(a, ...b) {}
+             f();",
+        );
+        compare_error(
+            "Expected at least 2 arguments but got 1. [line 2]",
+            "fun f(a, b, ...c) {}
+             f(1);",
+        );
+        compare_error(
+            "Expected 1 arguments but got 0. [line 2]",
+            "fun f(a) {}
+             f();",
+        );
+    }
+
+    #[test]
+    fn test_interpolation_errors() {
+        compare_error(
+            "[line 1] Error at 'c': Expect '}' after interpolated expression.",
+            r#"print "a${b c}";"#,
+        );
+        compare_error("[line 1] Error: Unterminated string.", r#"print "a${b"#);
+    }
+
+    #[test]
+    fn test_list_errors() {
+        compare_error("List index out of range. [line 1]", "print [1, 2][2];");
+        compare_error("List index out of range. [line 1]", "[1][-1] = 2;");
+        compare_error(
+            "List index must be an integer. [line 1]",
+            "print [1, 2][0.5];",
+        );
+        compare_error(
+            "Only lists and maps can be indexed. [line 1]",
+            "print \"ab\"[0];",
+        );
+        compare_error("Can't pop from an empty list. [line 1]", "[].pop();");
+        compare_error("List index out of range. [line 1]", "[1].insert(2, 0);");
+        compare_error("Undefined property 'size'. [line 1]", "print [].size();");
+        compare_error(
+            "[line 1] Error at ';': Expect ']' after list elements.",
+            "print [1, 2;",
+        );
+    }
+
+    #[test]
+    fn test_map_errors() {
+        compare_error("Undefined key 'z'. [line 1]", "print {\"a\": 1}[\"z\"];");
+        compare_error(
+            "Map keys can't be NaN. [line 2]",
+            "var nan = 0.0 / 0.0;
+             var m = {nan: 1};",
+        );
+        compare_error(
+            "Map keys can't be NaN. [line 1]",
+            "var m = {}; m[0.0 / 0.0] = 1;",
+        );
+        compare_error(
+            "[line 1] Error at '2': Expect ':' after map key.",
+            "print {1 2};",
+        );
+    }
+
+    #[test]
+    fn test_parse_errors_prevent_execution() {
+        compare_error(
+            "[line 2] Error at end: Expect '}' after body",
+            "print \"should not run\";
+             {",
+        );
+    }
+
+    #[test]
+    fn test_rendered_diagnostics() {
+        assert_eq!(
+            "Operands must be numbers. [line 2]\n\
+             2 | print a - \"b\";\n  \
+             |         ^\n\
+             at <script> line 2",
+            rendered("var a = 1;\nprint a - \"b\";")
+        );
+        assert_eq!(
+            "[line 1] Error: Unterminated string.\n\
+             1 | var s = \"e\n  \
+             |         ^~",
+            rendered("var s = \"e")
+        );
+        assert_eq!(
+            "[line 1] Error: Unexpected character.\n\
+             1 | var e = \"ü\" @;\n  \
+             |             ^",
+            rendered("var e = \"ü\" @;")
+        );
+        assert_eq!(
+            "[line 1] Error at 'missing': Expect ';' after value.\n\
+             1 | print \"u\" missing;\n  \
+             |           ^~~~~~~",
+            rendered("print \"u\" missing;")
+        );
+    }
+
+    #[test]
+    fn test_resolver_errors() {
+        compare_error(
+            "[line 1] Error at 'return': Can't return from top-level code.",
+            "return 1;",
+        );
+        compare_error(
+            "[line 2] Error at 'a': Can't read local variable in its own initializer.",
+            "{
+                var a = a;
+             }",
+        );
+        compare_error(
+            "[line 1] Error at 'this': Can't use 'this' outside of a class.",
+            "print this;",
+        );
+        compare_error(
+            "[line 1] Error at 'super': Can't use 'super' outside of a class.",
+            "super.cook();",
+        );
+        compare_error(
+            "[line 1] Error at 'super': Can't use 'super' in a class with no superclass.",
+            "class Bagel { cook() { super.cook(); } }",
+        );
+        compare_error(
+            "[line 1] Error at 'Bagel': A class can't inherit from itself.",
+            "class Bagel < Bagel {}",
+        );
+        compare_error(
+            "[line 1] Error at 'return': Can't return a value from an initializer.",
+            "class Bagel { init() { return 1; } }",
+        );
+        compare_error(
+            "[line 1] Error at 'break': Can't use 'break' outside of a loop.",
+            "if (true) break;",
+        );
+        compare_error(
+            "[line 2] Error at 'continue': Can't use 'continue' outside of a loop.",
+            "while (true) {
+                fun f() { continue; }
+             }",
+        );
+    }
+
+    #[test]
+    fn test_resolver_errors_are_collected() {
+        compare_error(
+            "[line 1] Error at 'return': Can't return from top-level code.\n\
+             [line 3] Error at 'this': Can't use 'this' outside of a class.",
+            "return;
+             print \"should not run\";
+             print this;",
+        );
+    }
+
+    #[test]
+    fn test_stack_trace() {
+        let source = "fun count(n) {
+                          if (n > 2) return n * nil;
+                          return count(n + 1);
+                      }
+                      fun start() {
+                          count(0);
+                      }
+                      start();";
+        match try_run(source).unwrap_err() {
+            LoxError::Runtime(error) => assert_eq!(
+                "at count() line 2\n\
+                 at count() line 3\n\
+                 at count() line 3\n\
+                 at count() line 3\n\
+                 at start() line 6\n\
+                 at <script> line 8",
+                error.traceback()
+            ),
+            error => panic!("Expected runtime error, got {error}"),
+        }
+    }
+
+    #[test]
+    fn test_stack_trace_through_initializer() {
+        let source = "class Bagel {
+                          init() {
+                              this.size = -\"large\";
+                          }
+                      }
+                      Bagel();";
+        match try_run(source).unwrap_err() {
+            LoxError::Runtime(error) => {
+                assert_eq!("at Bagel() line 3\nat <script> line 6", error.traceback())
+            }
+            error => panic!("Expected runtime error, got {error}"),
+        }
+    }
+
+    #[test]
+    fn test_stack_overflow() {
+        // A debug build needs more stack for `MAX_FRAMES` calls than a test thread has
+        let run = || {
+            let source = "fun f(n) {
+                              if (n == 0) return 0;
+                              return 1 + f(n - 1);
+                          }
+                          print f(100);
+                          f(100000);";
+            match try_run(source).unwrap_err() {
+                LoxError::Runtime(error) => {
+                    assert_eq!("Stack overflow. [line 3]", error.to_string());
+                    let trace = error.traceback();
+                    assert_eq!(MAX_FRAMES + 1, trace.lines().count());
+                    assert!(trace.starts_with("at f() line 3\n"));
+                    assert!(trace.ends_with("at <script> line 6"));
+                }
+                error => panic!("Expected runtime error, got {error}"),
+            }
+        };
+        std::thread::Builder::new()
+            .stack_size(256 << 20)
+            .spawn(run)
+            .unwrap()
+            .join()
+            .unwrap();
+    }
+}
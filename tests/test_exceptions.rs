@@ -0,0 +1,103 @@
+#[cfg(test)]
+mod test_exceptions {
+    use lox::testing_utils::{compare_error, compare_output};
+
+    #[test]
+    fn test_throw_and_catch() {
+        compare_output(
+            "caught boom\nafter\n",
+            "try {
+                throw \"boom\";
+                print \"not reached\";
+             } catch (e) {
+                print \"caught \" + e;
+             }
+             print \"after\";",
+        );
+    }
+
+    #[test]
+    fn test_catch_runtime_errors() {
+        compare_output(
+            "a\n\
+             Operands must be numbers. 3\n\
+             Undefined variable 'missing'. 6\n\
+             Expected 1 arguments but got 0. 10\n",
+            "try {
+                print \"a\";
+                1 - nil;
+             } catch (e) { print \"${e.message} ${e.line}\"; }
+             try {
+                print missing;
+             } catch (e) { print \"${e.message} ${e.line}\"; }
+             fun f(x) {}
+             try {
+                f();
+             } catch (e) { print \"${e.message} ${e.line}\"; }",
+        )
+    }
+
+    #[test]
+    fn test_throw_through_functions() {
+        compare_output(
+            "inner failed\n",
+            "fun inner() { throw \"inner failed\"; }
+             fun outer() {
+                while (true) { inner(); }
+             }
+             try { outer(); } catch (e) { print e; }",
+        );
+    }
+
+    #[test]
+    fn test_finally() {
+        compare_output(
+            "body\nfinally\nfinally\nthrown\ncleanup\n1\n",
+            "try { print \"body\"; } finally { print \"finally\"; }
+             try {
+                try { throw \"thrown\"; } finally { print \"finally\"; }
+             } catch (e) { print e; }
+             fun f() {
+                try { return 1; } finally { print \"cleanup\"; }
+             }
+             print f();",
+        );
+        compare_output(
+            "0\n1\n2\n",
+            "for (var i = 0; i < 3; i = i + 1) {
+                try {
+                    if (i == 2) break;
+                } finally {
+                    print i;
+                }
+             }",
+        );
+    }
+
+    #[test]
+    fn test_catch_scope() {
+        compare_output(
+            "caught\nouter\n",
+            "var e = \"outer\";
+             var status = \"\";
+             try { throw 1; } catch (e) { status = \"caught\"; }
+             print status;
+             print e;",
+        );
+    }
+
+    #[test]
+    fn test_uncaught_exceptions() {
+        compare_error("Uncaught exception: boom [line 1]", "throw \"boom\";");
+        compare_error(
+            "Operands must be numbers. [line 3]",
+            "try {
+                1 - nil;
+             } catch (e) { throw e; }",
+        );
+        compare_error(
+            "[line 1] Error at end: Expect 'catch' or 'finally' after try block.",
+            "try {}",
+        );
+    }
+}
@@ -1,6 +1,8 @@
 #[cfg(test)]
 mod test_expressions {
-    use lox::testing_utils::compare_output;
+    use lox::{
+        callable::Arity, session::Session, testing_utils::compare_output, tokens::LoxObject,
+    };
 
     #[test]
     fn test_arithmetic() {
@@ -49,4 +51,243 @@ mod test_expressions {
         compare_output("true\n", "print !false == true;");
         compare_output("false\n", "print 2 < 1 == true;");
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_string_escapes() {
+        compare_output(
+            "a\tb\n\"quoted\" \\ 😀\n",
+            r#"print "a\tb\n\"quoted\" \\ \u{1F600}";"#,
+        );
+        compare_output("true\n", r#"print "\u{e9}" == "é";"#);
+    }
+
+    #[test]
+    fn test_string_interpolation() {
+        compare_output(
+            "Hello Ann, you are 42!\n",
+            r#"var name = "Ann";
+               var age = 41;
+               print "Hello ${name}, you are ${age + 1}!";"#,
+        );
+        compare_output(
+            "in Ann out\n${x} $y niltrue\n",
+            r#"var name = "Ann";
+               print "${"in ${name}"} out";
+               print "\${x} $y ${nil}${true}";"#,
+        );
+    }
+
+    #[test]
+    fn test_number_literals() {
+        compare_output(
+            "255\n10\n15\n1000000\n0.0015\n2000\n255\n",
+            "print 0xFF; print 0b1010; print 0o17; print 1_000_000;
+             print 1.5e-3; print 2E3; print 0xf_f;",
+        );
+        compare_output(
+            "18446744073709551615\n-36893488147419103232\n",
+            "print 0xFFFFFFFFFFFFFFFF; print -0o4_000_000_000_000_000_000_000;",
+        );
+    }
+
+    #[test]
+    fn test_number_precision() {
+        compare_output(
+            "16777217\n0.30000000000000004\n2.5\n-3\nInfinity\nNaN\n",
+            "print 16777217; print 0.1 + 0.2; print 5.0 / 2; print -3.0;
+             print 1.0 / 0; print 0.0 / 0;",
+        );
+        compare_output(
+            "1.0E21\n-1.5E-5\n1.0E7\n9999999.5\n0.001\n1.2345E-4\n",
+            "print 1e21; print -1.5e-5; print 1e7; print 9999999.5;
+             print 0.001; print 0.00012345;",
+        );
+    }
+
+    #[test]
+    fn test_integer_arithmetic() {
+        compare_output(
+            "3\n-3\n1\n-1\n1.5\n1.5\n256\ntrue\ntrue\n9223372036854775806\n",
+            "print 7 / 2; print -7 / 2; print 7 % 3; print -7 % 3;
+             print 7.5 % 2; print 1 + 0.5; print 0xFF + 1;
+             print 1 == 1.0; print 2 > 1.5;
+             print 9223372036854775807 - 1;",
+        );
+    }
+
+    #[test]
+    fn test_big_integers() {
+        compare_output(
+            "15511210043330985984000000\n870\n",
+            "fun fact(n) { if (n <= 1) return 1; return n * fact(n - 1); }
+             print fact(25);
+             print fact(30) / fact(28);",
+        );
+        compare_output(
+            "9223372036854775808\n9223372036854775808\n-1\n",
+            "print 9223372036854775807 + 1;
+             print -(-9223372036854775807 - 1);
+             print -99999999999999999999 % 7;",
+        );
+        compare_output(
+            "true\ntrue\nfalse\n1.0E20\n",
+            "print 99999999999999999999 > 1.5;
+             print (5).toBigInt() == 5;
+             print 99999999999999999999 < 99999999999999999998;
+             print 100000000000000000000.toFloat();",
+        );
+        compare_output(
+            "2\n-2\n1.5\n",
+            "print 2.9.toInt(); print (-2.9).toBigInt(); print 3.toFloat() / 2;",
+        );
+        compare_output("2\n", "var toInt = 2.9.toInt; print toInt();");
+        compare_output(
+            "52601652673686724607999\n15142998474615178911756352\n\
+             -4294967294\n-39614081257132168792477007874\n",
+            "fun fact(n) { if (n <= 1) return 1; return n * fact(n - 1); }
+             var a = fact(40) + 12345;
+             var b = fact(25) + 7;
+             print a / b; print a % b;
+             var c = -0x7FFFFFFF800000000000000000000000;
+             var d = 0x800000000000000000000001;
+             print c / d; print c % d;",
+        );
+    }
+
+    #[test]
+    fn test_big_integers_shrink_back() {
+        let mut session = Session::new();
+        session
+            .interpreter
+            .define_native("isInt", Arity::Exact(1), |_, arguments| {
+                Ok(LoxObject::Bool(matches!(arguments[0], LoxObject::Int(_))))
+            });
+        assert_eq!(
+            "false\ntrue\ntrue\ntrue\n",
+            session
+                .run(
+                    "var big = 9223372036854775807 + 1;
+                     print isInt(big); print isInt(big - 1);
+                     print isInt(big * big / big / 2); print isInt(-big);"
+                )
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn test_lists() {
+        compare_output(
+            "[]\n[1, two, [3]]\n1\n3\n",
+            "print [];
+             var xs = [1, \"two\", [3]];
+             print xs;
+             print xs[0];
+             print xs[2][0];",
+        );
+        compare_output(
+            "5\n5\n[1, 5]\n",
+            "var xs = [1, 2];
+             print xs[1] = 5;
+             var ys = xs;
+             print ys[1];
+             print xs;",
+        );
+        compare_output(
+            "true\nfalse\n",
+            "var xs = [1]; print xs == xs; print xs == [1];",
+        );
+        compare_output(
+            "[1, [...]]\n[[[...]]]\n[[], []]\n",
+            "var xs = [1]; xs.push(xs); print xs;
+             var ys = []; var zs = [ys]; ys.push(zs); print [ys, zs][0];
+             var e = []; print [e, e];",
+        );
+    }
+
+    #[test]
+    fn test_list_methods() {
+        compare_output(
+            "3\n[0, 1, 2, 3]\n3\n[0, 1, 2]\n1\n[0, 2]\n",
+            "var xs = [1, 2];
+             xs.push(3);
+             print xs.len();
+             xs.insert(0, 0);
+             print xs;
+             print xs.pop();
+             print xs;
+             print xs.remove(1);
+             print xs;",
+        );
+        compare_output(
+            "[1, 2]\n",
+            "var xs = [];
+             var push = xs.push;
+             push(1);
+             push(2);
+             print xs;",
+        );
+    }
+
+    #[test]
+    fn test_maps() {
+        compare_output(
+            "{}\n{a: 1, b: 2}\n2\n{a: 3, b: 2, c: [4]}\n",
+            "print {};
+             var m = {\"a\": 1, \"b\": 2};
+             print m;
+             print m[\"b\"];
+             m[\"a\"] = 3;
+             m[\"c\"] = [4];
+             print m;",
+        );
+        compare_output(
+            "one\none\ntrue\nlist\nfoo\n",
+            "var m = {1: \"one\", true: \"yes\", nil: \"none\"};
+             print m[1];
+             print m[1.0];
+             print m[(2).toBigInt() - 1] == \"one\";
+             var xs = [];
+             class Foo {}
+             var foo = Foo();
+             m[xs] = \"list\";
+             m[foo] = \"foo\";
+             print m[xs];
+             print m[foo];",
+        );
+        compare_output(
+            "{self: {...}}\n{a: [{...}]}\n",
+            "var m = {}; m[\"self\"] = m; print m;
+             var n = {}; n[\"a\"] = [n]; print n;",
+        );
+        compare_output(
+            "false\ntrue\nfalse\ntrue\nfalse\nbig\nzero\n",
+            "print 9007199254740993 == 9007199254740992.0;
+             print 9007199254740993 > 9007199254740992.0;
+             print 9007199254740992.0 > 9007199254740993;
+             print 1180591620717411303424 == 1180591620717411303424.0;
+             var m = {9007199254740993: \"x\"};
+             print m.has(9007199254740992.0);
+             m[1180591620717411303424] = \"big\";
+             m[0] = \"zero\";
+             print m[1180591620717411303424.0];
+             print m[-0.0];",
+        );
+    }
+
+    #[test]
+    fn test_map_methods() {
+        compare_output(
+            "[b, a, c]\n[2, 1, 3]\ntrue\nfalse\n1\nnil\n[b, c]\n2\n",
+            "var m = {\"b\": 2, \"a\": 1};
+             m[\"c\"] = 3;
+             print m.keys();
+             print m.values();
+             print m.has(\"a\");
+             print m.has(\"z\");
+             print m.remove(\"a\");
+             print m.remove(\"a\");
+             print m.keys();
+             print m.len();",
+        );
+    }
+}
@@ -1,6 +1,8 @@
 #[cfg(test)]
 mod test_functions {
-    use lox::testing_utils::compare_output;
+    use lox::{
+        callable::Arity, session::Session, testing_utils::compare_output, tokens::LoxObject,
+    };
 
     #[test]
     fn test_function_declaration() {
@@ -35,6 +37,30 @@ mod test_functions {
         );
     }
 
+    #[test]
+    fn test_return_from_loops() {
+        compare_output(
+            "3\nfound\n",
+            "fun find(n) {
+                var i = 0;
+                while (true) {
+                    if (i == n) return i;
+                    i = i + 1;
+                }
+             }
+             fun search() {
+                for (var i = 0; i < 10; i = i + 1) {
+                    {
+                        if (i == 4) return \"found\";
+                    }
+                }
+                return \"missing\";
+             }
+             print find(3);
+             print search();",
+        );
+    }
+
     #[test]
     fn test_function_closure() {
         compare_output(
@@ -52,4 +78,158 @@ mod test_functions {
             counter();",
         );
     }
+
+    #[test]
+    fn test_function_recursion() {
+        compare_output(
+            "55\n",
+            "fun fib(n) {
+                if (n < 2) return n;
+                return fib(n - 1) + fib(n - 2);
+             }
+             print fib(10);",
+        );
+    }
+
+    #[test]
+    fn test_closures_share_their_scope() {
+        compare_output(
+            "true\nfalse\n",
+            "fun isEven(n) {
+                if (n == 0) return true;
+                return isOdd(n - 1);
+             }
+             fun isOdd(n) {
+                if (n == 0) return false;
+                return isEven(n - 1);
+             }
+             print isEven(4);
+             print isOdd(4);",
+        );
+        compare_output(
+            "2\n",
+            "{
+                var x = 1;
+                fun show() { print x; }
+                x = 2;
+                show();
+             }",
+        );
+        compare_output(
+            "global\nglobal\n",
+            "var a = \"global\";
+             {
+                fun showA() { print a; }
+                showA();
+                var a = \"block\";
+                showA();
+             }",
+        );
+    }
+
+    #[test]
+    fn test_clock() {
+        compare_output("true\n<native fn>\n", "print clock() > 0; print clock;");
+    }
+
+    #[test]
+    fn test_define_native() {
+        let mut session = Session::new();
+        session
+            .interpreter
+            .define_native("join", Arity::Exact(2), |_, arguments| {
+                match (&arguments[0], &arguments[1]) {
+                    (LoxObject::String(a), LoxObject::String(b)) => {
+                        Ok(LoxObject::String(format!("{a}-{b}")))
+                    }
+                    _ => Err("join() takes two strings.".to_string()),
+                }
+            });
+        assert_eq!(
+            "a-b\n",
+            session
+                .run("fun f() { return join(\"a\", \"b\"); } print f();")
+                .unwrap()
+        );
+
+        let error = session.run("join(1, 2);").unwrap_err();
+        assert_eq!("join() takes two strings. [line 2]", error.to_string());
+        let error = session.run("join(1);").unwrap_err();
+        assert_eq!(
+            "Expected 2 arguments but got 1. [line 3]",
+            error.to_string()
+        );
+
+        // Called straight from Rust there is no call site to report at
+        let join = session.interpreter.globals.borrow().values["join"].clone();
+        let LoxObject::Native(join) = join else {
+            panic!("join should be a native function");
+        };
+        let error = join
+            .call(
+                &mut session.interpreter,
+                vec![LoxObject::None, LoxObject::None],
+            )
+            .unwrap_err();
+        assert_eq!("join() takes two strings. [line 0]", error.to_string());
+    }
+
+    #[test]
+    fn test_rest_parameters() {
+        compare_output(
+            "warn: [disk, full]\ninfo: []\n",
+            "fun log(level, ...parts) { print level + \": \" + \"${parts}\"; }
+             log(\"warn\", \"disk\", \"full\");
+             log(\"info\");",
+        );
+        compare_output(
+            "[1, 2]\n",
+            "class Bag { init(...items) { this.items = items; } }
+             print Bag(1, 2).items;",
+        );
+    }
+
+    #[test]
+    fn test_variadic_native() {
+        let mut session = Session::new();
+        session
+            .interpreter
+            .define_native("max", Arity::AtLeast(1), |_, arguments| {
+                let mut max = arguments[0].clone();
+                for argument in arguments {
+                    if argument > max {
+                        max = argument;
+                    }
+                }
+                Ok(max)
+            });
+        session
+            .interpreter
+            .define_native("pad", Arity::Range(1, 2), |_, arguments| {
+                let width = match arguments.get(1) {
+                    Some(LoxObject::Int(width)) => *width as usize,
+                    _ => 8,
+                };
+                Ok(LoxObject::String(format!(
+                    "{:>width$}",
+                    arguments[0].to_string()
+                )))
+            });
+        assert_eq!(
+            "7\n3\n       x\n  y\n",
+            session
+                .run("print max(3, 7, 2); print max(3); print pad(\"x\"); print pad(\"y\", 3);")
+                .unwrap()
+        );
+        let error = session.run("max();").unwrap_err();
+        assert_eq!(
+            "Expected at least 1 argument but got 0. [line 2]",
+            error.to_string()
+        );
+        let error = session.run("pad(1, 2, 3);").unwrap_err();
+        assert_eq!(
+            "Expected 1 to 2 arguments but got 3. [line 3]",
+            error.to_string()
+        );
+    }
 }
@@ -0,0 +1,197 @@
+#[cfg(test)]
+mod test_session {
+    use lox::{
+        inspect,
+        repl::{is_complete, Command},
+        session::Session,
+    };
+
+    #[test]
+    fn test_session_keeps_variables() {
+        let mut session = Session::new();
+        assert_eq!("", session.run("var a = 1;\n").unwrap());
+        assert_eq!("1\n", session.run("print a;\n").unwrap());
+        assert_eq!("2\n", session.run("a = 2;\n").unwrap());
+        assert_eq!("2\n", session.run("print a;\n").unwrap());
+    }
+
+    #[test]
+    fn test_session_keeps_functions_and_classes() {
+        let mut session = Session::new();
+        session.run("fun add(a, b) { return a + b; }\n").unwrap();
+        session
+            .run("class Greeter { hi() { return \"hi\"; } }\n")
+            .unwrap();
+        assert_eq!("3\n", session.run("print add(1, 2);\n").unwrap());
+        assert_eq!("hi\n", session.run("print Greeter().hi();\n").unwrap());
+    }
+
+    #[test]
+    fn test_session_loads_scripts() {
+        let mut session = Session::new();
+        session.run("var a = 1;\n").unwrap();
+        assert_eq!(
+            "2\n",
+            session.run("#!/usr/bin/env lox\nprint a + 1;\n").unwrap()
+        );
+    }
+
+    #[test]
+    fn test_session_echoes_expressions() {
+        let mut session = Session::new();
+        assert_eq!("3\n", session.run("1 + 2;\n").unwrap());
+        assert_eq!("", session.run("nil;\n").unwrap());
+        assert_eq!("1\n", session.run("var a; a = 1;\n").unwrap());
+    }
+
+    #[test]
+    fn test_session_survives_errors() {
+        let mut session = Session::new();
+        session.run("var a = 1;\n").unwrap();
+        assert_eq!(
+            "Operands must be numbers. [line 2]",
+            session.run("a - nil;\n").unwrap_err().to_string()
+        );
+        assert_eq!(
+            "[line 3] Error at end: Expect ';' after value.",
+            session.run("print a").unwrap_err().to_string()
+        );
+        assert_eq!("1\n", session.run("print a;\n").unwrap());
+        assert_eq!(
+            "Operands must be numbers. [line 5]",
+            session
+                .run("{ var b = 2; while (true) { b - nil; } }\n")
+                .unwrap_err()
+                .to_string()
+        );
+        assert_eq!(
+            "Undefined variable 'b'. [line 6]",
+            session.run("print b;\n").unwrap_err().to_string()
+        );
+        // The error stops the input before `d` is declared
+        assert_eq!(
+            "Operands must be two numbers or two strings. [line 7]",
+            session
+                .run("var c = 1; nil + 1; var d = 2;\n")
+                .unwrap_err()
+                .to_string()
+        );
+        assert_eq!("1\n", session.run("print c;\n").unwrap());
+        assert_eq!(
+            "Undefined variable 'd'. [line 9]",
+            session.run("print d;\n").unwrap_err().to_string()
+        );
+        assert_eq!(
+            "Undefined variable 'd'. [line 10]",
+            session.run("d = 3;\n").unwrap_err().to_string()
+        );
+    }
+
+    #[test]
+    fn test_incomplete_input() {
+        assert!(!is_complete("fun add(a, b) {\n"));
+        assert!(!is_complete(
+            "class Bagel {\n  cook() {\n    print \"hi\";\n  }\n"
+        ));
+        assert!(!is_complete("print (1 +\n"));
+        assert!(!is_complete("print \"multi\nline"));
+        assert!(!is_complete("var xs = [1,\n"));
+        assert!(!is_complete("print 1\n"));
+        assert!(!is_complete("if (true)\n"));
+        assert!(!is_complete("/* open /* nested */\n"));
+        assert!(!is_complete("/// Documents the next line.\n"));
+    }
+
+    #[test]
+    fn test_complete_input() {
+        assert!(is_complete(""));
+        assert!(is_complete("print 1;\n"));
+        assert!(is_complete("fun add(a, b) {\n  return a + b;\n}\n"));
+        assert!(is_complete("print \"multi\nline\";"));
+        // Errors that more input can't fix are reported straight away
+        assert!(is_complete("print 1 2;\n"));
+        assert!(is_complete("}\n"));
+        assert!(is_complete("print @;\n"));
+    }
+
+    #[test]
+    fn test_parse_commands() {
+        assert_eq!(Ok(Command::Env), Command::parse(":env\n"));
+        assert_eq!(Ok(Command::Quit), Command::parse("  :quit  "));
+        assert_eq!(
+            Ok(Command::Tokens("print 1;".to_string())),
+            Command::parse(":tokens print 1;")
+        );
+        assert_eq!(
+            Ok(Command::Load("script.lox".to_string())),
+            Command::parse(":load script.lox\n")
+        );
+        assert_eq!(
+            Err("Usage: :load <file>".to_string()),
+            Command::parse(":load")
+        );
+        assert_eq!(
+            Ok(Command::Help("Point.init".to_string())),
+            Command::parse(":help Point.init")
+        );
+        assert_eq!(
+            Err("Unknown command ':nope'.".to_string()),
+            Command::parse(":nope")
+        );
+    }
+
+    #[test]
+    fn test_doc_comments() {
+        let mut session = Session::new();
+        session
+            .run(
+                "/// Adds two numbers.
+                 ///    Indented.
+                 fun add(a, b) { return a + b; }
+                 //// Not a doc comment.
+                 fun sub(a, b) { return a - b; }
+                 /// A point.
+                 class Point {
+                   /// Makes one.
+                   init(x) { this.x = x; }
+                 }",
+            )
+            .unwrap();
+        assert_eq!(Some("Adds two numbers.\n   Indented."), session.doc("add"));
+        assert_eq!(None, session.doc("sub"));
+        assert_eq!(Some("A point."), session.doc("Point"));
+        assert_eq!(Some("Makes one."), session.doc("Point.init"));
+    }
+
+    #[test]
+    fn test_inspect() {
+        assert_eq!(
+            "1 0..5 Print print nil\n\
+             1 6..7 Number 1 1\n\
+             1 7..8 Semicolon ; nil\n\
+             1 8..8 Eof  nil\n",
+            inspect::tokens("print 1;").unwrap()
+        );
+        assert_eq!(
+            "Var(a = Binary(Literal(1) + Literal(2)))\n\
+             Fun(f(x) Return(Call(Var(g) (Var(x)))))\n",
+            inspect::ast("var a = 1 + 2; fun f(x) { return g(x); }").unwrap()
+        );
+        assert_eq!(
+            "1 21..22 a 1\n\
+             1 41..42 b 1\n\
+             1 45..46 a 2\n",
+            inspect::resolved("var a = 1; { var b = a; fun f() { return b + a; } }").unwrap()
+        );
+    }
+
+    #[test]
+    fn test_env_display() {
+        let mut session = Session::new();
+        session.run("var b = \"two\"; var a = 1;").unwrap();
+        assert_eq!(
+            "{a: 1, b: two} -> {clock: <native fn>}",
+            session.interpreter.environment.borrow().to_string()
+        );
+    }
+}
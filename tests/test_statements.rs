@@ -1,6 +1,6 @@
 #[cfg(test)]
 mod test_statements {
-    use lox::testing_utils::compare_output;
+    use lox::{testing_utils::compare_output, try_run_with_args};
 
     #[test]
     fn test_print_statement() {
@@ -68,6 +68,35 @@ mod test_statements {
         );
     }
 
+    #[test]
+    fn test_break_and_continue() {
+        compare_output(
+            "0\n1\n",
+            "var i = 0;
+             while (true) {
+                if (i == 2) break;
+                print i;
+                i = i + 1;
+             }",
+        );
+        compare_output(
+            "0\n2\n4\n",
+            "for (var i = 0; i < 6; i = i + 1) {
+                if (i % 2 == 1) continue;
+                print i;
+             }",
+        );
+        compare_output(
+            "0 0\n1 0\n2 0\n",
+            "for (var i = 0; i < 3; i = i + 1) {
+                for (var j = 0; j < 3; j = j + 1) {
+                    if (j > 0) break;
+                    print \"${i} ${j}\";
+                }
+             }",
+        );
+    }
+
     #[test]
     fn test_nested_blocks() {
         compare_output(
@@ -98,4 +127,34 @@ mod test_statements {
              }",
         );
     }
+
+    #[test]
+    fn test_shebang_line() {
+        compare_output("3\n", "#!/usr/bin/env lox\nprint 1 + 2;");
+    }
+
+    #[test]
+    fn test_script_args() {
+        compare_output("[]\n", "print args;");
+        let args = vec!["a".to_string(), "b".to_string()];
+        assert_eq!("[a, b]\n", try_run_with_args("print args;", &args).unwrap());
+    }
+
+    #[test]
+    fn test_non_ascii_source() {
+        compare_output(
+            "héllo 😀\n",
+            "// ünïcode comment
+             print \"héllo 😀\";",
+        );
+    }
+
+    #[test]
+    fn test_block_comments() {
+        compare_output(
+            "1\n2\n",
+            "print 1; /* a /* nested */ comment
+             spanning lines */ print 2;",
+        );
+    }
 }